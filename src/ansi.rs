@@ -0,0 +1,288 @@
+use unicode_width::UnicodeWidthStr;
+
+/// Resets every SGR attribute.
+pub const RESET: &str = "\x1b[0m";
+
+/// Ends an OSC 8 hyperlink.
+pub const LINK_END: &str = "\x1b]8;;\x1b\\";
+
+pub const BOLD: &str = "\x1b[1m";
+pub const DIM: &str = "\x1b[2m";
+pub const RED: &str = "\x1b[31m";
+
+/// A dark gray background.
+pub const STRIPE: &str = "\x1b[48;5;236m";
+
+/// A piece of text that is either an escape sequence or visible text.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Segment<'a> {
+	Escape(&'a str),
+	Text(&'a str),
+}
+
+/// Splits `text` into escape sequences and the visible text between them.
+///
+/// Recognized sequences are CSI sequences such as SGR styles (`ESC [ 31 m`), OSC sequences
+/// such as OSC 8 hyperlinks, ended by `ESC \` or BEL, and other two-character escapes. An
+/// unterminated sequence runs to the end of the text.
+pub fn segments(text: &str) -> Vec<Segment<'_>> {
+	let mut segments = Vec::new();
+	let mut rest = text;
+
+	while !rest.is_empty() {
+		let Some(start) = rest.find('\x1b') else {
+			segments.push(Segment::Text(rest));
+			break;
+		};
+		if start > 0 {
+			segments.push(Segment::Text(&rest[..start]));
+		}
+
+		let end = start + escape_length(&rest[start..]);
+		segments.push(Segment::Escape(&rest[start..end]));
+		rest = &rest[end..];
+	}
+
+	segments
+}
+
+/// Returns the length in bytes of the escape sequence at the start of `text`.
+fn escape_length(text: &str) -> usize {
+	let body = &text[1..];
+
+	let length = if let Some(params) = body.strip_prefix('[') {
+		// Parameters and intermediates up to a final byte in '@'..='~'
+		params.find(|c: char| ('@'..='~').contains(&c)).map(|end| 1 + end + 1)
+	} else if let Some(command) = body.strip_prefix(']') {
+		let bel = command.find('\x07').map(|end| end + 1);
+		let st = command.find("\x1b\\").map(|end| end + 2);
+		match (bel, st) {
+			(Some(bel), Some(st)) => Some(1 + bel.min(st)),
+			(end, None) | (None, end) => end.map(|end| 1 + end),
+		}
+	} else {
+		body.chars().next().map(char::len_utf8)
+	};
+
+	length.map_or(text.len(), |length| 1 + length)
+}
+
+/// Removes every escape sequence from `text`.
+pub fn strip(text: &str) -> String {
+	segments(text).into_iter()
+		.filter_map(|segment| match segment {
+			Segment::Text(text) => Some(text),
+			Segment::Escape(_) => None,
+		})
+		.collect()
+}
+
+/// Returns the display width of `text`, not counting escape sequences.
+pub fn width(text: &str) -> usize {
+	segments(text).into_iter()
+		.map(|segment| match segment {
+			Segment::Text(text) => text.width(),
+			Segment::Escape(_) => 0,
+		})
+		.sum()
+}
+
+/// The SGR styles and hyperlink in effect at some point of a text.
+#[derive(Clone, Debug, Default)]
+pub struct Style {
+	/// SGR sequences applied since the last reset.
+	sgr: String,
+
+	/// The OSC 8 sequence of the open hyperlink.
+	link: Option<String>,
+}
+
+impl Style {
+	/// Updates the style with an escape sequence, ignoring sequences other than SGR and OSC 8.
+	pub fn apply(&mut self, escape: &str) {
+		if is_reset(escape) {
+			self.sgr.clear();
+		} else if escape.starts_with("\x1b[") && escape.ends_with('m') {
+			self.sgr.push_str(escape);
+		} else if let Some(command) = escape.strip_prefix("\x1b]8;") {
+			// OSC 8 ; params ; URI, an empty URI ends the link
+			let uri = command.split_once(';').map_or("", |(_, uri)| uri);
+			let uri = uri.trim_end_matches(['\x07', '\\']).trim_end_matches('\x1b');
+			self.link = if uri.is_empty() { None } else { Some(escape.to_string()) };
+		}
+	}
+
+	/// Returns the escape sequences that restore this style.
+	pub fn open(&self) -> String {
+		format!("{}{}", self.sgr, self.link.as_deref().unwrap_or(""))
+	}
+
+	/// Returns the escape sequences that end this style.
+	pub fn close(&self) -> String {
+		let link_end = if self.link.is_some() { LINK_END } else { "" };
+		let reset = if self.sgr.is_empty() { "" } else { RESET };
+		format!("{}{}", link_end, reset)
+	}
+}
+
+/// Checks for an SGR sequence that resets every attribute: `ESC [ m` or `ESC [ 0 m`.
+fn is_reset(escape: &str) -> bool {
+	escape.strip_prefix("\x1b[")
+		.and_then(|rest| rest.strip_suffix('m'))
+		.is_some_and(|params| params.split(';').all(|param| param.trim_start_matches('0').is_empty()))
+}
+
+/// Returns the style left open at the end of `text`.
+pub fn style_at_end(text: &str) -> Style {
+	let mut style = Style::default();
+	for segment in segments(text) {
+		if let Segment::Escape(escape) = segment {
+			style.apply(escape);
+		}
+	}
+	style
+}
+
+/// Appends the sequences that end the styles left open in `text`.
+pub fn close(text: &str) -> String {
+	format!("{}{}", text, style_at_end(text).close())
+}
+
+/// Makes every line stand on its own: styles left open at the end of a line are closed there
+/// and reopened at the start of the next line.
+pub fn carry<S: AsRef<str>>(lines: &[S]) -> Vec<String> {
+	let mut style = Style::default();
+
+	lines.iter()
+		.map(|line| {
+			let line = line.as_ref();
+			let opened = style.open();
+			for segment in segments(line) {
+				if let Segment::Escape(escape) = segment {
+					style.apply(escape);
+				}
+			}
+			format!("{}{}{}", opened, line, style.close())
+		})
+		.collect()
+}
+
+/// Shows every line of `text` in `style`, an SGR sequence.
+///
+/// The style is applied again after each reset inside the line, so styled text keeps its
+/// background and weight.
+pub fn paint(text: &str, style: &str) -> String {
+	text.split('\n')
+		.map(|line| {
+			let mut painted = String::from(style);
+			for segment in segments(line) {
+				match segment {
+					Segment::Text(text) => painted.push_str(text),
+					Segment::Escape(escape) => {
+						painted.push_str(escape);
+						if is_reset(escape) {
+							painted.push_str(style);
+						}
+					},
+				}
+			}
+			painted.push_str(RESET);
+			painted
+		})
+		.collect::<Vec<_>>()
+		.join("\n")
+}
+
+/// Builds the SGR sequence for a list of style names separated by commas or spaces, such as
+/// `bold,red`, `bold red` or `on-yellow`.
+///
+/// The names are `bold`, `dim`, `italic`, `underline`, `reverse`, `strike`, the colors
+/// `black`, `red`, `green`, `yellow`, `blue`, `magenta`, `cyan` and `white`, and the same
+/// colors prefixed with `on-` as backgrounds.
+pub fn parse_style(text: &str) -> Result<String, String> {
+	const COLORS: [&str; 8] = ["black", "red", "green", "yellow", "blue", "magenta", "cyan", "white"];
+
+	let names: Vec<&str> = text.split([',', ' ']).filter(|name| !name.trim().is_empty()).collect();
+	if names.is_empty() {
+		return Err(format!("Invalid style: {}", text));
+	}
+
+	let codes = names.into_iter()
+		.map(|name| {
+			let name = name.trim().to_lowercase();
+			let code = match name.as_str() {
+				"bold"      => 1,
+				"dim"       => 2,
+				"italic"    => 3,
+				"underline" => 4,
+				"reverse"   => 7,
+				"strike"    => 9,
+				_ => match name.strip_prefix("on-") {
+					Some(color) => COLORS.iter().position(|c| *c == color).map(|i| 40 + i),
+					None => COLORS.iter().position(|c| *c == name).map(|i| 30 + i),
+				}.ok_or_else(|| format!("Invalid style: {}", name))?,
+			};
+			Ok(code.to_string())
+		})
+		.collect::<Result<Vec<String>, String>>()?;
+
+	Ok(format!("\x1b[{}m", codes.join(";")))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const LINK: &str = "\x1b]8;;https://example.com\x1b\\";
+
+	#[test]
+	fn segments_split_escapes_from_text() {
+		assert_eq!(segments("a\x1b[1;31mb\x1b[0m"), [
+			Segment::Text("a"),
+			Segment::Escape("\x1b[1;31m"),
+			Segment::Text("b"),
+			Segment::Escape("\x1b[0m"),
+		]);
+		assert_eq!(segments("\x1b]8;;x\x07y"), [Segment::Escape("\x1b]8;;x\x07"), Segment::Text("y")]);
+		assert_eq!(segments("a\x1b[31"), [Segment::Text("a"), Segment::Escape("\x1b[31")]);
+	}
+
+	#[test]
+	fn hyperlinks_only_count_their_text() {
+		let link = format!("{}site 中{}", LINK, LINK_END);
+		assert_eq!(width(&link), 7);
+		assert_eq!(strip(&link), "site 中");
+		assert_eq!(width(&format!("{}bold{}", BOLD, RESET)), 4);
+	}
+
+	#[test]
+	fn style_at_end_tracks_resets_and_links() {
+		assert_eq!(style_at_end(&format!("{}a{}b", BOLD, RED)).open(), format!("{}{}", BOLD, RED));
+		assert_eq!(style_at_end(&format!("{}a{}b", BOLD, RESET)).open(), "");
+		assert_eq!(style_at_end(&format!("{}a", LINK)).open(), LINK);
+		assert_eq!(style_at_end(&format!("{}a{}", LINK, LINK_END)).open(), "");
+		assert_eq!(close(&format!("{}{}a", RED, LINK)), format!("{}{}a{}{}", RED, LINK, LINK_END, RESET));
+	}
+
+	#[test]
+	fn carry_reopens_styles_on_the_next_line() {
+		let lines = [format!("{}one", RED), "two".to_string(), format!("three{}", RESET), "four".to_string()];
+		assert_eq!(carry(&lines), [
+			format!("{}one{}", RED, RESET),
+			format!("{}two{}", RED, RESET),
+			format!("{}three{}", RED, RESET),
+			"four".to_string(),
+		]);
+
+		let lines = [format!("{}see", LINK), format!("here{}", LINK_END)];
+		assert_eq!(carry(&lines), [format!("{}see{}", LINK, LINK_END), format!("{}here{}", LINK, LINK_END)]);
+	}
+	#[test]
+	fn styles_are_parsed_from_names() {
+		assert_eq!(parse_style("bold red").as_deref(), Ok("\x1b[1;31m"));
+		assert_eq!(parse_style("bold, on-Yellow").as_deref(), Ok("\x1b[1;43m"));
+		assert_eq!(parse_style("blinking").unwrap_err(), "Invalid style: blinking");
+		assert_eq!(parse_style("on-pink").unwrap_err(), "Invalid style: on-pink");
+		assert!(parse_style(" ,").is_err());
+	}
+}
@@ -1,5 +1,6 @@
 use clap::{Args, Parser, Subcommand};
-use crate::text::{center, clean, Frame, left, right, truncate, wrap, text, TextFormatter};
+use crate::text::{Alignment, center, clean, Frame, justify, left, right, truncate, wrap, text, TextFormatter};
+use crate::output::OutputFormat;
 use crate::table::TableBuilder;
 use eyre::Result;
 
@@ -14,6 +15,7 @@ pub struct Cli {
 }
 
 #[derive(Subcommand)]
+#[allow(clippy::large_enum_variant)]
 pub enum FmtCommands {
     /// Sanitizes the input text by removing leading and trailing blank lines and whitespace
     Clean(CleanCli),
@@ -78,6 +80,22 @@ pub struct WrapCli {
 
     #[arg(short, long)]
     pub width: Option<usize>,
+
+    /// Stretch the spaces between words so every line but the last of a paragraph fills the width
+    #[arg(long)]
+    pub justify: bool,
+
+    /// Alignment of the last line of each paragraph of justified text
+    #[arg(long, value_enum)]
+    pub last_line: Option<Alignment>,
+
+    /// Wrap paragraphs as a whole, keeping blank lines, list items, indentation and code blocks
+    #[arg(long)]
+    pub reflow: bool,
+
+    /// Rewrap comment blocks, keeping prefixes such as `// `, `# `, ` * ` or `> ` on every line
+    #[arg(long)]
+    pub comment: bool,
 }
 
 #[derive(Args)]
@@ -98,6 +116,10 @@ pub struct TruncateCli {
     #[arg(short, long)]
     pub no_ellipsis: Option<bool>,
 
+    /// Marker for truncated text, `{}` stands for the number of hidden characters
+    #[arg(long)]
+    pub ellipsis: Option<String>,
+
     #[arg(short, long)]
     pub frame: Option<Frame>,
 }
@@ -137,30 +159,61 @@ impl Cli {
           input.text.as_deref(),
           input.width,
           input.no_ellipsis,
+          input.ellipsis.as_deref(),
           input.frame,
         ));
       },
       FmtCommands::Wrap(input) => {
-        println!("{}", wrap(input.text.as_deref(), input.width));
+        if input.justify {
+          println!("{}", justify(input.text.as_deref(), input.width, input.last_line, Some(input.reflow), Some(input.comment)));
+        } else {
+          println!("{}", wrap(input.text.as_deref(), input.width, Some(input.reflow), Some(input.comment)));
+        }
       },
       FmtCommands::Text(input) => {
+        // Apply the locale preset over the individual separators
+        let mut input = input.clone();
+        if let Some(locale) = input.locale {
+          input.set_locale(locale);
+        }
+        if let Some(locale) = input.input_locale {
+          input.set_input_locale(locale);
+        }
         println!("{}", text(
           input.text.as_deref(),
           Some(input.width),
           Some(input.frame),
           Some(input.no_ellipsis),
+          Some(input.ellipsis.as_str()),
+          input.max_lines,
+          Some(input.reflow),
+          Some(input.comment),
           Some(input.pad_decimal_digits),
           Some(input.max_decimal_digits),
           Some(input.decimal_separator),
           Some(input.use_thousand_separator),
           Some(input.thousand_separator),
+          Some(input.grouping),
+          input.input_decimal_separator,
+          input.input_thousand_separator,
+          Some(input.number_display),
+          input.currency.as_deref(),
+          Some(input.currency_position),
+          Some(input.accounting),
+          Some(input.plus_sign),
+          Some(input.rounding),
+          Some(input.strict),
+          input.sig_figs,
           Some(input.alignment),
+          Some(input.last_line),
         ));
       },
       FmtCommands::Table(input) => {
         let mut table = TableBuilder::new(input.input.clone())
           .set_ifs(input.ifs.clone())
           .set_ofs(input.ofs.clone())
+          .set_input_format(input.input_format)
+          .set_field_widths(input.field_widths.clone())
           .set_header_index(input.header_index)
           .set_header_count(input.header_count)
           .set_column_width_limits_index(input.column_width_limits_index)
@@ -169,16 +222,60 @@ impl Cli {
           .set_max_cell_width(input.max_cell_width)
           .set_frame(input.frame)
           .set_no_ellipsis(input.no_ellipsis)
+          .set_ellipsis(input.ellipsis.clone())
+          .set_max_lines(input.max_lines)
           .set_alignment(input.alignment)
           .set_pad_decimal_digits(input.pad_decimal_digits)
           .set_max_decimal_digits(input.max_decimal_digits)
           .set_decimal_separator(input.decimal_separator)
           .set_use_thousand_separator(input.use_thousand_separator)
           .set_thousand_separator(input.thousand_separator)
+          .set_grouping(input.grouping)
+          .set_input_decimal_separator(input.input_decimal_separator)
+          .set_input_thousand_separator(input.input_thousand_separator)
+          .set_number_display(input.number_display)
+          .set_column_displays(input.column_displays.clone())
+          .set_currency(input.currency.clone())
+          .set_currency_position(input.currency_position)
+          .set_accounting(input.accounting)
+          .set_plus_sign(input.plus_sign)
+          .set_rounding(input.rounding)
+          .set_strict(input.strict)
+          .set_sig_figs(input.sig_figs)
+          .set_auto_precision(input.auto_precision)
+          .set_type_threshold(input.type_threshold)
+          .set_sort(input.sort.clone(), input.sort_descending)
+          .set_date_format(input.date_format.clone())
+          .set_relative_time(input.relative_time, input.reference_time)
+          .set_timezone(input.timezone)
+          .set_epoch_columns(input.epoch_columns.clone())
+          .set_null_values(input.null_values.clone())
+          .set_null_display(input.null_display.clone())
+          .set_footer_count(input.footer_count)
+          .set_ragged(input.ragged)
+          .set_output(input.output)
+          .set_standalone(input.standalone)
+          .set_inline_styles(input.inline_styles)
+          .set_booktabs(input.booktabs)
+          .set_siunitx(input.siunitx)
+          .set_color(input.color)
+          .set_highlights(input.highlights.clone())
           .clone();
 
-        let built_table = table.build();
-        built_table.printstd();
+        // Apply the locale preset over the individual separators
+        if let Some(locale) = input.locale {
+          table.set_locale(locale);
+        }
+        if let Some(locale) = input.input_locale {
+          table.set_input_locale(locale);
+        }
+
+        table.validate()?;
+
+        match input.output {
+          OutputFormat::PLAIN => print!("{}", table.render()),
+          _ => println!("{}", table.render()),
+        }
       },
     }
     Ok(()) // Return Ok if everything executes successfully
@@ -0,0 +1,165 @@
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime};
+use std::fmt::Write;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Date-time layouts without an offset, read as UTC.
+const NAIVE_FORMATS: [&str; 5] = [
+	"%Y-%m-%dT%H:%M:%S%.f",
+	"%Y-%m-%d %H:%M:%S%.f",
+	"%Y-%m-%dT%H:%M",
+	"%Y-%m-%d %H:%M",
+	"%d/%b/%Y:%H:%M:%S",
+];
+
+/// Reads a timestamp in one of the formats found in logs.
+///
+/// The formats are RFC 3339 (`2024-03-01T14:05:09+02:00`), ISO 8601 dates and date-times
+/// without an offset, read as UTC, and the Apache log format (`01/Mar/2024:14:05:09 +0200`).
+/// With `epoch`, integers are also read as seconds since the Unix epoch, or as milliseconds
+/// when they have 12 digits or more.
+///
+/// # Returns
+///
+/// The timestamp with its offset, or `None` when `text` is not a timestamp.
+pub fn parse(text: &str, epoch: bool) -> Option<DateTime<FixedOffset>> {
+	let text = text.trim();
+
+	if epoch && !text.is_empty() && text.chars().all(|c| c.is_ascii_digit()) {
+		let value: i64 = text.parse().ok()?;
+		let utc = if text.len() >= 12 {
+			DateTime::from_timestamp_millis(value)
+		} else {
+			DateTime::from_timestamp(value, 0)
+		};
+		return utc.map(|utc| utc.fixed_offset());
+	}
+
+	if let Ok(timestamp) = DateTime::parse_from_rfc3339(text) {
+		return Some(timestamp);
+	}
+
+	if let Ok(timestamp) = DateTime::parse_from_str(text, "%d/%b/%Y:%H:%M:%S %z") {
+		return Some(timestamp);
+	}
+
+	NAIVE_FORMATS.iter()
+		.find_map(|format| NaiveDateTime::parse_from_str(text, format).ok())
+		.or_else(|| NaiveDate::parse_from_str(text, "%Y-%m-%d").ok()?.and_hms_opt(0, 0, 0))
+		.map(|naive| naive.and_utc().fixed_offset())
+}
+
+/// Formats `timestamp` with a `strftime` pattern.
+///
+/// # Returns
+///
+/// The formatted timestamp, or `None` when the pattern is invalid.
+pub fn format(timestamp: &DateTime<FixedOffset>, pattern: &str) -> Option<String> {
+	let mut formatted = String::new();
+	write!(formatted, "{}", timestamp.format(pattern)).ok()?;
+	Some(formatted)
+}
+
+/// Describes `timestamp` relative to `reference`, in its largest whole unit: `3m ago`,
+/// `in 2h`, or `now` within a second.
+pub fn relative(timestamp: &DateTime<FixedOffset>, reference: &DateTime<FixedOffset>) -> String {
+	const UNITS: [(&str, i64); 6] = [
+		("y", 365 * 86_400),
+		("mo", 30 * 86_400),
+		("d", 86_400),
+		("h", 3_600),
+		("m", 60),
+		("s", 1),
+	];
+
+	let seconds = reference.signed_duration_since(timestamp).num_seconds();
+	let Some((name, size)) = UNITS.iter().find(|(_, size)| seconds.abs() >= *size) else {
+		return "now".to_string();
+	};
+
+	let count = seconds.abs() / size;
+	if seconds > 0 {
+		format!("{}{} ago", count, name)
+	} else {
+		format!("in {}{}", count, name)
+	}
+}
+
+/// Returns the current time, in UTC.
+pub fn now() -> DateTime<FixedOffset> {
+	let seconds = SystemTime::now()
+		.duration_since(UNIX_EPOCH)
+		.map(|elapsed| elapsed.as_secs() as i64)
+		.unwrap_or(0);
+
+	DateTime::from_timestamp(seconds, 0).unwrap_or_default().fixed_offset()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn at(text: &str) -> DateTime<FixedOffset> {
+		DateTime::parse_from_rfc3339(text).unwrap()
+	}
+
+	#[test]
+	fn rfc3339_keeps_its_offset() {
+		let timestamp = parse("2024-03-01T14:05:09.25+02:00", false).unwrap();
+		assert_eq!(timestamp.to_rfc3339(), "2024-03-01T14:05:09.250+02:00");
+		assert_eq!(parse(" 2024-03-01T14:05:09Z ", false), Some(at("2024-03-01T14:05:09Z")));
+	}
+
+	#[test]
+	fn apache_log_format() {
+		assert_eq!(parse("01/Mar/2024:14:05:09 +0200", false), Some(at("2024-03-01T14:05:09+02:00")));
+	}
+
+	#[test]
+	fn naive_formats_are_utc() {
+		assert_eq!(parse("2024-03-01 14:05:09", false), Some(at("2024-03-01T14:05:09Z")));
+		assert_eq!(parse("2024-03-01T14:05:09.5", false), Some(at("2024-03-01T14:05:09.5Z")));
+		assert_eq!(parse("2024-03-01 14:05", false), Some(at("2024-03-01T14:05:00Z")));
+		assert_eq!(parse("01/Mar/2024:14:05:09", false), Some(at("2024-03-01T14:05:09Z")));
+		assert_eq!(parse("2024-03-01", false), Some(at("2024-03-01T00:00:00Z")));
+		assert_eq!(parse("2024-02-30", false), None);
+		assert_eq!(parse("yesterday", false), None);
+	}
+
+	#[test]
+	fn epoch_seconds_and_millis() {
+		assert_eq!(parse("1709301909", true), Some(at("2024-03-01T14:05:09Z")));
+		assert_eq!(parse("1709301909250", true), Some(at("2024-03-01T14:05:09.250Z")));
+		assert_eq!(parse("1709301909", false), None);
+	}
+
+	#[test]
+	fn twelve_digits_are_millis() {
+		// 11 digits are seconds, far in the future, 12 digits are millis in 1973
+		assert_eq!(parse("10000000000", true), Some(at("2286-11-20T17:46:40Z")));
+		assert_eq!(parse("100000000000", true), Some(at("1973-03-03T09:46:40Z")));
+	}
+
+	#[test]
+	fn format_with_pattern() {
+		let timestamp = at("2024-03-01T14:05:09+02:00");
+		assert_eq!(format(&timestamp, "%Y/%m/%d %H:%M %z").as_deref(), Some("2024/03/01 14:05 +0200"));
+		assert_eq!(format(&timestamp, "%Q"), None);
+	}
+
+	#[test]
+	fn relative_uses_the_largest_whole_unit() {
+		let reference = at("2024-03-01T12:00:00Z");
+		let relative_to = |text: &str| relative(&at(text), &reference);
+		assert_eq!(relative_to("2024-03-01T12:00:00.900Z"), "now");
+		assert_eq!(relative_to("2024-03-01T11:59:59Z"), "1s ago");
+		assert_eq!(relative_to("2024-03-01T11:59:01Z"), "59s ago");
+		assert_eq!(relative_to("2024-03-01T11:59:00Z"), "1m ago");
+		assert_eq!(relative_to("2024-03-01T11:00:01Z"), "59m ago");
+		assert_eq!(relative_to("2024-03-01T11:00:00Z"), "1h ago");
+		assert_eq!(relative_to("2024-02-29T12:00:01Z"), "23h ago");
+		assert_eq!(relative_to("2024-02-29T12:00:00Z"), "1d ago");
+		assert_eq!(relative_to("2024-03-04T12:00:00Z"), "in 3d");
+		assert_eq!(relative_to("2024-04-01T12:00:00Z"), "in 1mo");
+		assert_eq!(relative_to("2023-03-01T12:00:00Z"), "1y ago");
+	}
+}
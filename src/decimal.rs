@@ -0,0 +1,368 @@
+use clap::ValueEnum;
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+
+#[allow(non_camel_case_types)]
+#[derive(Copy, Clone, Default, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+pub enum Rounding {
+	/// Round halves away from zero: `2.5` → `3`.
+	#[default]
+	HALF_UP,
+
+	/// Round halves to the nearest even digit: `2.5` → `2`, `3.5` → `4`.
+	HALF_EVEN,
+
+	/// Drop the extra digits: `2.9` → `2`.
+	TRUNCATE,
+}
+
+impl FromStr for Rounding {
+	type Err = String;
+
+	fn from_str(text: &str) -> Result<Rounding, Self::Err> {
+		match text.to_uppercase().replace('-', "_").as_str() {
+			"HALF_UP"   => Ok(Rounding::HALF_UP),
+			"HALF_EVEN" => Ok(Rounding::HALF_EVEN),
+			"TRUNCATE"  => Ok(Rounding::TRUNCATE),
+			_ => Err(format!("Invalid rounding: {}", text)),
+		}
+	}
+}
+
+impl fmt::Display for Rounding {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Rounding::HALF_UP   => write!(f, "HALF-UP"  ),
+			Rounding::HALF_EVEN => write!(f, "HALF-EVEN"),
+			Rounding::TRUNCATE  => write!(f, "TRUNCATE" ),
+		}
+	}
+}
+
+/// A decimal number of any precision, kept as its digits.
+///
+/// Numbers are read and printed digit by digit, so long IDs such as `12345678901234567890`
+/// and values such as `0.1` are shown exactly as written.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Decimal {
+	/// Whether the number is below zero, never set for zero.
+	negative: bool,
+
+	/// Digits before the decimal point, without leading zeros, `0` for none.
+	integer: String,
+
+	/// Digits after the decimal point, without trailing zeros.
+	fraction: String,
+}
+
+impl Decimal {
+	/// Reads a decimal number: an optional sign, digits and an optional decimal point.
+	///
+	/// Unless `strict`, an exponent such as `1.5e3` is also accepted and applied exactly.
+	/// `inf` and `NaN` are never read, they have no digits.
+	///
+	/// # Returns
+	///
+	/// The number, or `None` when `text` is not a decimal number.
+	pub fn parse(text: &str, strict: bool) -> Option<Decimal> {
+		let (negative, unsigned) = match text.strip_prefix('-') {
+			Some(rest) => (true, rest),
+			None => (false, text.strip_prefix('+').unwrap_or(text)),
+		};
+
+		let (mantissa, exponent) = match unsigned.find(['e', 'E']) {
+			Some(_) if strict => return None,
+			Some(i) => (&unsigned[..i], Some(&unsigned[i + 1..])),
+			None => (unsigned, None),
+		};
+
+		let (integer, fraction) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+		let is_digits = |part: &str| part.chars().all(|c| c.is_ascii_digit());
+		if integer.is_empty() && fraction.is_empty() || !is_digits(integer) || !is_digits(fraction) {
+			return None;
+		}
+
+		let number = Decimal::from_parts(negative, integer, fraction);
+
+		match exponent {
+			Some(exponent) => {
+				let digits = exponent.strip_prefix(['+', '-']).unwrap_or(exponent);
+				if digits.is_empty() || !is_digits(digits) {
+					return None;
+				}
+				// Larger exponents would spell out an unreasonable number of zeros
+				let places: i64 = exponent.parse().ok().filter(|places: &i64| places.abs() <= 4096)?;
+				Some(number.shift(places))
+			},
+			None => Some(number),
+		}
+	}
+
+	/// Converts a finite `f64` through its shortest exact representation.
+	pub fn from_f64(number: f64) -> Decimal {
+		Decimal::parse(&number.to_string(), true).unwrap_or_else(|| Decimal::from_parts(false, "0", ""))
+	}
+
+	/// Converts the number to the nearest `f64`.
+	pub fn to_f64(&self) -> f64 {
+		self.to_string().parse().unwrap_or(0.0)
+	}
+
+	/// Builds a normalized number from its sign and digits.
+	fn from_parts(negative: bool, integer: &str, fraction: &str) -> Decimal {
+		let integer = integer.trim_start_matches('0');
+		let fraction = fraction.trim_end_matches('0');
+		let is_zero = integer.is_empty() && fraction.is_empty();
+
+		Decimal {
+			negative: negative && !is_zero,
+			integer: if integer.is_empty() { "0".to_string() } else { integer.to_string() },
+			fraction: fraction.to_string(),
+		}
+	}
+
+	pub fn is_zero(&self) -> bool {
+		self.integer == "0" && self.fraction.is_empty()
+	}
+
+	pub fn is_negative(&self) -> bool {
+		self.negative
+	}
+
+	/// The digits before the decimal point.
+	pub fn integer(&self) -> &str {
+		&self.integer
+	}
+
+	/// The digits after the decimal point, without trailing zeros.
+	pub fn fraction(&self) -> &str {
+		&self.fraction
+	}
+
+	/// Returns the magnitude of the number.
+	pub fn abs(&self) -> Decimal {
+		Decimal { negative: false, ..self.clone() }
+	}
+
+	/// Multiplies the number by `10^places` by moving the decimal point.
+	pub fn shift(&self, places: i64) -> Decimal {
+		let digits = format!("{}{}", self.integer, self.fraction);
+		let point = self.integer.len() as i64 + places;
+
+		if point <= 0 {
+			let zeros = "0".repeat(point.unsigned_abs() as usize);
+			Decimal::from_parts(self.negative, "", &format!("{}{}", zeros, digits))
+		} else if point as usize >= digits.len() {
+			let zeros = "0".repeat(point as usize - digits.len());
+			Decimal::from_parts(self.negative, &format!("{}{}", digits, zeros), "")
+		} else {
+			let (integer, fraction) = digits.split_at(point as usize);
+			Decimal::from_parts(self.negative, integer, fraction)
+		}
+	}
+
+	/// The power of ten of the first significant digit: `2` for `123`, `-3` for `0.00123`.
+	///
+	/// Zero has exponent `0`.
+	pub fn exponent(&self) -> i64 {
+		if self.integer != "0" {
+			self.integer.len() as i64 - 1
+		} else {
+			match self.fraction.find(|c| c != '0') {
+				Some(zeros) => -(zeros as i64) - 1,
+				None => 0,
+			}
+		}
+	}
+
+	/// Rounds the number to at most `digits` fractional digits.
+	///
+	/// Rounding applies to the magnitude, so `-2.5` rounds half up to `-3`.
+	pub fn round(&self, digits: usize, rounding: Rounding) -> Decimal {
+		if self.fraction.len() <= digits {
+			return self.clone();
+		}
+
+		let (kept, dropped) = self.fraction.split_at(digits);
+		let first_dropped = dropped.as_bytes()[0];
+		let kept_digits = format!("{}{}", self.integer, kept);
+
+		let round_up = match rounding {
+			Rounding::TRUNCATE => false,
+			Rounding::HALF_UP => first_dropped >= b'5',
+			Rounding::HALF_EVEN => {
+				let last_kept = kept_digits.as_bytes()[kept_digits.len() - 1];
+				first_dropped > b'5'
+					|| first_dropped == b'5' && (dropped[1..].contains(|c| c != '0') || (last_kept - b'0') % 2 == 1)
+			},
+		};
+
+		let rounded = if round_up { increment(&kept_digits) } else { kept_digits };
+		let (integer, fraction) = rounded.split_at(rounded.len() - digits);
+		Decimal::from_parts(self.negative, integer, fraction)
+	}
+
+	/// Rounds the number to `figures` significant figures: `12345` to 3 figures is `12300`.
+	pub fn round_significant(&self, figures: usize, rounding: Rounding) -> Decimal {
+		// Move the last significant figure just before the decimal point, round, move it back
+		let places = figures as i64 - 1 - self.exponent();
+		self.shift(places).round(0, rounding).shift(-places)
+	}
+}
+
+/// Adds one to a string of decimal digits.
+fn increment(digits: &str) -> String {
+	let mut bytes = digits.as_bytes().to_vec();
+
+	for byte in bytes.iter_mut().rev() {
+		if *byte == b'9' {
+			*byte = b'0';
+		} else {
+			*byte += 1;
+			return String::from_utf8(bytes).unwrap_or_default();
+		}
+	}
+
+	// Every digit carried over
+	format!("1{}", String::from_utf8(bytes).unwrap_or_default())
+}
+
+impl Ord for Decimal {
+	fn cmp(&self, other: &Self) -> Ordering {
+		// Longer integers are larger, fractions without trailing zeros compare as text
+		let magnitude = self.integer.len().cmp(&other.integer.len())
+			.then_with(|| self.integer.cmp(&other.integer))
+			.then_with(|| self.fraction.cmp(&other.fraction));
+
+		match (self.negative, other.negative) {
+			(false, false) => magnitude,
+			(true, true)   => magnitude.reverse(),
+			(false, true)  => Ordering::Greater,
+			(true, false)  => Ordering::Less,
+		}
+	}
+}
+
+impl PartialOrd for Decimal {
+	fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+		Some(self.cmp(other))
+	}
+}
+
+impl fmt::Display for Decimal {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let sign = if self.negative { "-" } else { "" };
+		if self.fraction.is_empty() {
+			write!(f, "{}{}", sign, self.integer)
+		} else {
+			write!(f, "{}{}.{}", sign, self.integer, self.fraction)
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn number(text: &str) -> Decimal {
+		Decimal::parse(text, false).unwrap()
+	}
+
+	fn round(text: &str, digits: usize, rounding: Rounding) -> String {
+		number(text).round(digits, rounding).to_string()
+	}
+
+	#[test]
+	fn parse_normalizes_digits() {
+		assert_eq!(number("007.500").to_string(), "7.5");
+		assert_eq!(number("+.5").to_string(), "0.5");
+		assert_eq!(number("-0.000").to_string(), "0");
+		assert!(!number("-0").is_negative());
+		assert_eq!(Decimal::parse("1.2.3", false), None);
+		assert_eq!(Decimal::parse("inf", false), None);
+		assert_eq!(Decimal::parse("", false), None);
+		assert_eq!(Decimal::parse(".", false), None);
+	}
+
+	#[test]
+	fn long_integers_are_exact() {
+		assert_eq!(number("12345678901234567890123").to_string(), "12345678901234567890123");
+		assert_eq!(number("-98765432109876543210.000000000000000001").to_string(), "-98765432109876543210.000000000000000001");
+	}
+
+	#[test]
+	fn exponents_shift_the_point() {
+		assert_eq!(number("1.5e3").to_string(), "1500");
+		assert_eq!(number("1.5E-3").to_string(), "0.0015");
+		assert_eq!(number("-25e-1").to_string(), "-2.5");
+		assert_eq!(number("123.456e+2").to_string(), "12345.6");
+		assert_eq!(Decimal::parse("1e5", true), None);
+		assert_eq!(Decimal::parse("1e", false), None);
+		assert_eq!(Decimal::parse("1e99999", false), None);
+	}
+
+	#[test]
+	fn shift_and_exponent() {
+		assert_eq!(number("123").shift(-5).to_string(), "0.00123");
+		assert_eq!(number("0.00123").shift(5).to_string(), "123");
+		assert_eq!(number("1.23").shift(4).to_string(), "12300");
+		assert_eq!(number("123").exponent(), 2);
+		assert_eq!(number("0.00123").exponent(), -3);
+		assert_eq!(number("0").exponent(), 0);
+	}
+
+	#[test]
+	fn half_up_rounds_ties_away_from_zero() {
+		assert_eq!(round("2.5", 0, Rounding::HALF_UP), "3");
+		assert_eq!(round("-2.5", 0, Rounding::HALF_UP), "-3");
+		assert_eq!(round("2.449", 1, Rounding::HALF_UP), "2.4");
+	}
+
+	#[test]
+	fn half_even_rounds_ties_to_even() {
+		assert_eq!(round("2.5", 0, Rounding::HALF_EVEN), "2");
+		assert_eq!(round("3.5", 0, Rounding::HALF_EVEN), "4");
+		assert_eq!(round("-0.125", 2, Rounding::HALF_EVEN), "-0.12");
+		assert_eq!(round("0.135", 2, Rounding::HALF_EVEN), "0.14");
+		// Not a tie once any later digit is set
+		assert_eq!(round("2.5000001", 0, Rounding::HALF_EVEN), "3");
+	}
+
+	#[test]
+	fn truncate_drops_digits() {
+		assert_eq!(round("2.99", 1, Rounding::TRUNCATE), "2.9");
+		assert_eq!(round("-2.99", 0, Rounding::TRUNCATE), "-2");
+	}
+
+	#[test]
+	fn rounding_carries_into_the_integer() {
+		assert_eq!(round("9.995", 2, Rounding::HALF_UP), "10");
+		assert_eq!(round("-999.96", 1, Rounding::HALF_UP), "-1000");
+		assert_eq!(round("0.0004", 3, Rounding::HALF_UP), "0");
+		assert!(!number("-0.0004").round(3, Rounding::HALF_UP).is_negative());
+	}
+
+	#[test]
+	fn increment_carries() {
+		assert_eq!(increment("129"), "130");
+		assert_eq!(increment("999"), "1000");
+	}
+
+	#[test]
+	fn significant_figures() {
+		assert_eq!(number("12345").round_significant(3, Rounding::HALF_UP).to_string(), "12300");
+		assert_eq!(number("0.0012345").round_significant(2, Rounding::HALF_UP).to_string(), "0.0012");
+		assert_eq!(number("9.96").round_significant(2, Rounding::HALF_UP).to_string(), "10");
+	}
+
+	#[test]
+	fn ordering_compares_values() {
+		assert!(number("10") > number("9.99"));
+		assert!(number("0.5") > number("0.49"));
+		assert!(number("-1") < number("0"));
+		assert!(number("-10") < number("-9"));
+		assert_eq!(number("1.50").cmp(&number("1.5")), Ordering::Equal);
+		assert!(number("12345678901234567890") > number("12345678901234567889"));
+	}
+}
@@ -0,0 +1,397 @@
+use chrono::{DateTime, FixedOffset};
+use clap::ValueEnum;
+use crate::datetime;
+use crate::decimal::Decimal;
+use std::cmp::Ordering;
+use std::fmt;
+use std::net::IpAddr;
+use std::str::FromStr;
+
+/// Cell values that stand for a missing value, skipped when inferring column types.
+pub const NULL_VALUES: [&str; 7] = ["", "-", "NULL", "null", "N/A", "n/a", "NA"];
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+pub enum ColumnType {
+	/// Anything else.
+	#[default]
+	TEXT,
+
+	/// Whole numbers: `42`.
+	INTEGER,
+
+	/// Numbers with decimal digits: `3.14`. Columns mixing integers and decimals are decimal.
+	DECIMAL,
+
+	/// Numbers followed by a percent sign: `12.5%`.
+	PERCENTAGE,
+
+	/// `true`, `false`, `yes` or `no`, in any case.
+	BOOLEAN,
+
+	/// ISO 8601 dates: `2024-03-01`.
+	DATE,
+
+	/// Times of day: `14:05` or `14:05:09.5`.
+	TIME,
+
+	/// Timestamps: RFC 3339 or ISO 8601 `2024-03-01T14:05:09Z`, or Apache log
+	/// `01/Mar/2024:14:05:09 +0000`.
+	DATETIME,
+
+	/// Amounts of time in units: `3h 12m`, `250ms`.
+	DURATION,
+
+	/// IPv4 or IPv6 addresses.
+	IP,
+
+	/// UUIDs: `123e4567-e89b-12d3-a456-426614174000`.
+	UUID,
+}
+
+impl FromStr for ColumnType {
+	type Err = String;
+
+	fn from_str(text: &str) -> Result<ColumnType, Self::Err> {
+		match text.to_uppercase().as_str() {
+			"TEXT"       => Ok(ColumnType::TEXT),
+			"INTEGER"    => Ok(ColumnType::INTEGER),
+			"DECIMAL"    => Ok(ColumnType::DECIMAL),
+			"PERCENTAGE" => Ok(ColumnType::PERCENTAGE),
+			"BOOLEAN"    => Ok(ColumnType::BOOLEAN),
+			"DATE"       => Ok(ColumnType::DATE),
+			"TIME"       => Ok(ColumnType::TIME),
+			"DATETIME"   => Ok(ColumnType::DATETIME),
+			"DURATION"   => Ok(ColumnType::DURATION),
+			"IP"         => Ok(ColumnType::IP),
+			"UUID"       => Ok(ColumnType::UUID),
+			_ => Err(format!("Invalid column type: {}", text)),
+		}
+	}
+}
+
+impl fmt::Display for ColumnType {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			ColumnType::TEXT       => write!(f, "TEXT"      ),
+			ColumnType::INTEGER    => write!(f, "INTEGER"   ),
+			ColumnType::DECIMAL    => write!(f, "DECIMAL"   ),
+			ColumnType::PERCENTAGE => write!(f, "PERCENTAGE"),
+			ColumnType::BOOLEAN    => write!(f, "BOOLEAN"   ),
+			ColumnType::DATE       => write!(f, "DATE"      ),
+			ColumnType::TIME       => write!(f, "TIME"      ),
+			ColumnType::DATETIME   => write!(f, "DATETIME"  ),
+			ColumnType::DURATION   => write!(f, "DURATION"  ),
+			ColumnType::IP         => write!(f, "IP"        ),
+			ColumnType::UUID       => write!(f, "UUID"      ),
+		}
+	}
+}
+
+impl ColumnType {
+	/// Whether the column holds numbers that are formatted by the number options.
+	pub fn is_number(&self) -> bool {
+		matches!(self, ColumnType::INTEGER | ColumnType::DECIMAL)
+	}
+
+	/// Whether the column holds quantities, which are aligned to the right.
+	pub fn is_numeric(&self) -> bool {
+		matches!(self, ColumnType::INTEGER | ColumnType::DECIMAL | ColumnType::PERCENTAGE | ColumnType::DURATION)
+	}
+
+	/// Detects the type of a single non-null cell.
+	///
+	/// # Arguments
+	///
+	/// * `cell` - The trimmed cell text.
+	/// * `number` - Reads a number the way the table reads numbers, with its separators.
+	pub fn detect(cell: &str, number: &dyn Fn(&str) -> Option<Decimal>) -> ColumnType {
+		if let Some(value) = number(cell) {
+			return if value.fraction().is_empty() { ColumnType::INTEGER } else { ColumnType::DECIMAL };
+		}
+
+		if cell.strip_suffix('%').is_some_and(|value| number(value.trim_end()).is_some()) {
+			ColumnType::PERCENTAGE
+		} else if matches!(cell.to_lowercase().as_str(), "true" | "false" | "yes" | "no") {
+			ColumnType::BOOLEAN
+		} else if is_date(cell) {
+			ColumnType::DATE
+		} else if is_time(cell) {
+			ColumnType::TIME
+		} else if datetime::parse(cell, false).is_some() {
+			ColumnType::DATETIME
+		} else if duration_seconds(cell).is_some() {
+			ColumnType::DURATION
+		} else if cell.parse::<IpAddr>().is_ok() {
+			ColumnType::IP
+		} else if is_uuid(cell) {
+			ColumnType::UUID
+		} else {
+			ColumnType::TEXT
+		}
+	}
+
+	/// Infers the type of a column from its cells.
+	///
+	/// Null cells are skipped. The most common type of the other cells wins when it covers at
+	/// least `threshold` of them, integers counting as decimals in a column that has decimals.
+	/// Otherwise, and for columns of nulls only, the column is text.
+	///
+	/// # Arguments
+	///
+	/// * `cells` - The cells of the column.
+	/// * `nulls` - Cell values that stand for a missing value.
+	/// * `threshold` - The share of non-null cells the winning type needs, from 0 to 1.
+	/// * `number` - Reads a number the way the table reads numbers, with its separators.
+	pub fn infer<'a>(
+		cells: impl Iterator<Item = &'a str>,
+		nulls: &[&str],
+		threshold: f64,
+		number: &dyn Fn(&str) -> Option<Decimal>,
+	) -> ColumnType {
+		let mut counts: Vec<(ColumnType, usize)> = Vec::new();
+		let mut total = 0;
+
+		for cell in cells.map(str::trim).filter(|cell| !nulls.contains(cell)) {
+			let cell_type = ColumnType::detect(cell, number);
+			match counts.iter_mut().find(|(t, _)| *t == cell_type) {
+				Some((_, count)) => *count += 1,
+				None => counts.push((cell_type, 1)),
+			}
+			total += 1;
+		}
+
+		// Integers in a decimal column are decimals too
+		let integers = counts.iter().find(|(t, _)| *t == ColumnType::INTEGER).map(|(_, count)| *count);
+		if let (Some(integers), Some((_, decimals))) = (integers, counts.iter_mut().find(|(t, _)| *t == ColumnType::DECIMAL)) {
+			*decimals += integers;
+			counts.retain(|(t, _)| *t != ColumnType::INTEGER);
+		}
+
+		match counts.into_iter().max_by(|a, b| a.1.cmp(&b.1).then(b.0.cmp(&a.0))) {
+			Some((cell_type, count)) if count as f64 >= threshold * total as f64 => cell_type,
+			_ => ColumnType::TEXT,
+		}
+	}
+
+	/// Compares two cells of a column of this type, nulls last in either order.
+	pub fn compare(
+		&self,
+		a: &str,
+		b: &str,
+		nulls: &[&str],
+		descending: bool,
+		number: &dyn Fn(&str) -> Option<Decimal>,
+	) -> Ordering {
+		let (a, b) = (a.trim(), b.trim());
+		let order = self.sort_key(a, number).cmp(&self.sort_key(b, number));
+
+		nulls.contains(&a).cmp(&nulls.contains(&b))
+			.then(if descending { order.reverse() } else { order })
+	}
+
+	/// Returns the value a cell is sorted by.
+	///
+	/// Cells that do not read as the column type sort after the ones that do, by their text.
+	fn sort_key(&self, cell: &str, number: &dyn Fn(&str) -> Option<Decimal>) -> SortKey {
+		let key = match self {
+			ColumnType::INTEGER | ColumnType::DECIMAL => number(cell).map(SortKey::Number),
+			ColumnType::PERCENTAGE => cell.strip_suffix('%').and_then(|value| number(value.trim_end())).map(SortKey::Number),
+			ColumnType::DURATION => duration_seconds(cell).map(|seconds| SortKey::Number(Decimal::from_f64(seconds))),
+			ColumnType::IP => cell.parse::<IpAddr>().ok().map(SortKey::Address),
+			ColumnType::BOOLEAN => Some(SortKey::Number(Decimal::from_f64(
+				if matches!(cell.to_lowercase().as_str(), "true" | "yes") { 1.0 } else { 0.0 }))),
+			ColumnType::UUID => Some(SortKey::Text(cell.to_lowercase())),
+			// Timestamps sort by the moment they stand for, whatever their format and offset
+			ColumnType::DATE | ColumnType::DATETIME => datetime::parse(cell, true).map(SortKey::Time),
+			ColumnType::TIME | ColumnType::TEXT => Some(SortKey::Text(cell.to_string())),
+		};
+		key.unwrap_or_else(|| SortKey::Other(cell.to_string()))
+	}
+}
+
+/// A cell value to sort by, in the order of the variants.
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+enum SortKey {
+	Number(Decimal),
+	Time(DateTime<FixedOffset>),
+	Address(IpAddr),
+	Text(String),
+	Other(String),
+}
+
+/// Checks for an ISO 8601 date: `2024-03-01`.
+fn is_date(text: &str) -> bool {
+	let parts: Vec<&str> = text.split('-').collect();
+	parts.len() == 3
+		&& parts[0].len() == 4
+		&& parts[1].len() == 2
+		&& parts[2].len() == 2
+		&& in_range(parts[0], 0, 9999)
+		&& in_range(parts[1], 1, 12)
+		&& in_range(parts[2], 1, 31)
+}
+
+/// Checks for a time of day: `14:05`, `14:05:09` or `14:05:09.250`.
+fn is_time(text: &str) -> bool {
+	let (clock, fraction) = text.split_once('.').unwrap_or((text, "0"));
+	let parts: Vec<&str> = clock.split(':').collect();
+	(parts.len() == 2 || parts.len() == 3)
+		&& parts.iter().all(|part| part.len() == 2)
+		&& in_range(parts[0], 0, 23)
+		&& in_range(parts[1], 0, 59)
+		&& parts.get(2).is_none_or(|seconds| in_range(seconds, 0, 60))
+		&& !fraction.is_empty()
+		&& fraction.chars().all(|c| c.is_ascii_digit())
+}
+
+/// Checks for a UUID: 32 hex digits in groups of 8, 4, 4, 4 and 12.
+fn is_uuid(text: &str) -> bool {
+	let groups: Vec<&str> = text.split('-').collect();
+	groups.iter().map(|group| group.len()).eq([8, 4, 4, 4, 12])
+		&& groups.iter().all(|group| group.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+/// Reads a duration made of numbers with units, such as `3h 12m` or `1.5s`, as seconds.
+///
+/// The units are `w`, `d`, `h`, `m`, `s`, `ms`, `us`, `µs` and `ns`.
+pub fn duration_seconds(text: &str) -> Option<f64> {
+	let mut seconds = 0.0;
+	let mut rest = text.trim();
+
+	if rest.is_empty() {
+		return None;
+	}
+
+	while !rest.is_empty() {
+		let digits = rest.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(rest.len());
+		let amount: f64 = rest[..digits].parse().ok()?;
+		rest = &rest[digits..];
+
+		let unit = rest.find(|c: char| c.is_ascii_digit() || c.is_whitespace()).unwrap_or(rest.len());
+		let scale = match &rest[..unit] {
+			"w"         => 604_800.0,
+			"d"         => 86_400.0,
+			"h"         => 3_600.0,
+			"m"         => 60.0,
+			"s"         => 1.0,
+			"ms"        => 1e-3,
+			"us" | "µs" => 1e-6,
+			"ns"        => 1e-9,
+			_ => return None,
+		};
+		seconds += amount * scale;
+		rest = rest[unit..].trim_start();
+	}
+
+	Some(seconds)
+}
+
+/// Checks that `text` is all digits and its value lies between `min` and `max`.
+fn in_range(text: &str, min: u32, max: u32) -> bool {
+	!text.is_empty()
+		&& text.chars().all(|c| c.is_ascii_digit())
+		&& text.parse::<u32>().is_ok_and(|value| min <= value && value <= max)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn number(text: &str) -> Option<Decimal> {
+		Decimal::parse(text, false)
+	}
+
+	fn infer(cells: &[&str], threshold: f64) -> ColumnType {
+		ColumnType::infer(cells.iter().copied(), &NULL_VALUES, threshold, &number)
+	}
+
+	fn sorted<'a>(column_type: ColumnType, cells: &[&'a str], descending: bool) -> Vec<&'a str> {
+		let mut cells = cells.to_vec();
+		cells.sort_by(|a, b| column_type.compare(a, b, &NULL_VALUES, descending, &number));
+		cells
+	}
+
+	#[test]
+	fn cells_are_detected() {
+		assert_eq!(ColumnType::detect("42", &number), ColumnType::INTEGER);
+		assert_eq!(ColumnType::detect("3.14", &number), ColumnType::DECIMAL);
+		assert_eq!(ColumnType::detect("12.5 %", &number), ColumnType::PERCENTAGE);
+		assert_eq!(ColumnType::detect("Yes", &number), ColumnType::BOOLEAN);
+		assert_eq!(ColumnType::detect("2024-03-01", &number), ColumnType::DATE);
+		assert_eq!(ColumnType::detect("14:05:09.5", &number), ColumnType::TIME);
+		assert_eq!(ColumnType::detect("2024-03-01T14:05:09Z", &number), ColumnType::DATETIME);
+		assert_eq!(ColumnType::detect("3h 12m", &number), ColumnType::DURATION);
+		assert_eq!(ColumnType::detect("::1", &number), ColumnType::IP);
+		assert_eq!(ColumnType::detect("123e4567-e89b-12d3-a456-426614174000", &number), ColumnType::UUID);
+		assert_eq!(ColumnType::detect("2024-13-01", &number), ColumnType::TEXT);
+	}
+
+	#[test]
+	fn winning_type_needs_the_threshold() {
+		assert_eq!(infer(&["1", "2", "x"], 0.6), ColumnType::INTEGER);
+		assert_eq!(infer(&["1", "2", "x"], 0.7), ColumnType::TEXT);
+		// Ties go to the type listed first
+		assert_eq!(infer(&["1", "2", "x", "y"], 0.5), ColumnType::TEXT);
+		assert_eq!(infer(&["1", "2", "true", "no"], 0.5), ColumnType::INTEGER);
+	}
+
+	#[test]
+	fn nulls_are_skipped() {
+		assert_eq!(infer(&["1", "NULL", "-", "", "n/a", "2"], 1.0), ColumnType::INTEGER);
+		assert_eq!(infer(&[" NA ", "null"], 0.5), ColumnType::TEXT);
+		assert_eq!(infer(&[], 0.5), ColumnType::TEXT);
+	}
+
+	#[test]
+	fn integers_and_decimals_merge_to_decimal() {
+		assert_eq!(infer(&["1", "2.5", "3", "4"], 1.0), ColumnType::DECIMAL);
+		assert_eq!(infer(&["1", "2", "3"], 1.0), ColumnType::INTEGER);
+	}
+
+	#[test]
+	fn mixed_columns_are_text() {
+		assert_eq!(infer(&["1", "2024-03-01", "true", "x"], 0.5), ColumnType::TEXT);
+		assert_eq!(infer(&["1", "2024-03-01"], 1.0), ColumnType::TEXT);
+	}
+
+	#[test]
+	fn numbers_sort_by_value_with_nulls_last() {
+		let cells = ["10", "NULL", "9", "-1.5", "x"];
+		assert_eq!(sorted(ColumnType::INTEGER, &cells, false), ["-1.5", "9", "10", "x", "NULL"]);
+		assert_eq!(sorted(ColumnType::DECIMAL, &cells, true), ["x", "10", "9", "-1.5", "NULL"]);
+		assert_eq!(sorted(ColumnType::PERCENTAGE, &["50%", "-", "5%"], false), ["5%", "50%", "-"]);
+	}
+
+	#[test]
+	fn durations_sort_by_length() {
+		let cells = ["1h", "n/a", "90s", "2m", "250ms"];
+		assert_eq!(sorted(ColumnType::DURATION, &cells, false), ["250ms", "90s", "2m", "1h", "n/a"]);
+		assert_eq!(sorted(ColumnType::DURATION, &cells, true), ["1h", "2m", "90s", "250ms", "n/a"]);
+	}
+
+	#[test]
+	fn timestamps_sort_by_moment() {
+		let cells = ["2024-03-01T13:00:00Z", "", "2024-03-01T14:00:00+02:00", "2024-03-01"];
+		assert_eq!(
+			sorted(ColumnType::DATETIME, &cells, false),
+			["2024-03-01", "2024-03-01T14:00:00+02:00", "2024-03-01T13:00:00Z", ""],
+		);
+		assert_eq!(sorted(ColumnType::DATE, &["2024-03-02", "N/A", "2023-12-31"], true), ["2024-03-02", "2023-12-31", "N/A"]);
+	}
+
+	#[test]
+	fn text_sorts_by_characters() {
+		assert_eq!(sorted(ColumnType::TEXT, &["b", "NULL", "a", "B"], false), ["B", "a", "b", "NULL"]);
+		assert_eq!(sorted(ColumnType::TEXT, &["b", "NULL", "a", "B"], true), ["b", "a", "B", "NULL"]);
+	}
+
+	#[test]
+	fn durations_are_read_as_seconds() {
+		assert_eq!(duration_seconds("3h 12m"), Some(11_520.0));
+		assert_eq!(duration_seconds("1m30s"), Some(90.0));
+		assert_eq!(duration_seconds("250ms"), Some(0.25));
+		assert_eq!(duration_seconds("3x"), None);
+		assert_eq!(duration_seconds("h"), None);
+		assert_eq!(duration_seconds(""), None);
+	}
+}
@@ -78,9 +78,9 @@ pub fn binary_or_text<T: AsRef<[u8]>>(data: T) -> Result<Data> {
 
     // Attempt to interpret the bytes as UTF-8
     if let Ok(text_data) = String::from_utf8(buf.clone()) {
-        return Ok(Data::Text(text_data)); // Valid UTF-8, return the String
+        Ok(Data::Text(text_data)) // Valid UTF-8, return the String
     } else {
-        return Ok(Data::Binary(buf)); // Treat as binary
+        Ok(Data::Binary(buf)) // Treat as binary
     }
 }
 
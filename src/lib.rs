@@ -1,5 +1,13 @@
+#![allow(clippy::upper_case_acronyms)]
+pub mod ansi;
 pub mod cli;
+pub mod datetime;
+pub mod decimal;
+pub mod infer;
 //pub mod io;
 pub mod input;
+pub mod output;
+pub mod paragraph;
+pub mod parse;
 pub mod table;
 pub mod text;
@@ -1,6 +1,14 @@
+#![allow(clippy::upper_case_acronyms)]
+mod ansi;
 mod cli;
+mod datetime;
+mod decimal;
+mod infer;
 //mod io;
 mod input;
+mod output;
+mod paragraph;
+mod parse;
 mod table;
 mod text;
 use eyre::Result;
@@ -0,0 +1,1000 @@
+use clap::ValueEnum;
+use crate::ansi;
+use crate::infer::ColumnType;
+use crate::table::TableBuilder;
+use crate::text::{Frame, TextFormatter};
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Copy, Clone, Default, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+#[allow(non_camel_case_types)]
+pub enum OutputFormat {
+	/// Plain text columns separated by the output field separator.
+	#[default]
+	PLAIN,
+
+	/// An HTML `<table>` with `<thead>`, `<tbody>` and `<tfoot>` sections.
+	HTML,
+
+	/// A LaTeX `tabular` environment.
+	LATEX,
+
+	/// A Markdown pipe table.
+	MARKDOWN,
+
+	/// An Org-mode table.
+	ORG,
+
+	/// A reStructuredText grid table, supporting multi-line cells.
+	RST_GRID,
+
+	/// A reStructuredText simple table.
+	RST_SIMPLE,
+
+	/// An AsciiDoc table.
+	ASCIIDOC,
+}
+
+impl FromStr for OutputFormat {
+	type Err = String;
+
+	fn from_str(text: &str) -> Result<OutputFormat, Self::Err> {
+		match text.to_uppercase().as_str() {
+			"PLAIN" => Ok(OutputFormat::PLAIN),
+			"HTML"  => Ok(OutputFormat::HTML),
+			"LATEX" => Ok(OutputFormat::LATEX),
+			"MARKDOWN"   => Ok(OutputFormat::MARKDOWN),
+			"ORG"        => Ok(OutputFormat::ORG),
+			"RST-GRID"   => Ok(OutputFormat::RST_GRID),
+			"RST-SIMPLE" => Ok(OutputFormat::RST_SIMPLE),
+			"ASCIIDOC"   => Ok(OutputFormat::ASCIIDOC),
+			_ => Err(format!("Invalid output format: {}", text)),
+		}
+	}
+}
+
+impl fmt::Display for OutputFormat {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			OutputFormat::PLAIN => write!(f, "PLAIN"),
+			OutputFormat::HTML  => write!(f, "HTML" ),
+			OutputFormat::LATEX => write!(f, "LATEX"),
+			OutputFormat::MARKDOWN   => write!(f, "MARKDOWN"  ),
+			OutputFormat::ORG        => write!(f, "ORG"       ),
+			OutputFormat::RST_GRID   => write!(f, "RST-GRID"  ),
+			OutputFormat::RST_SIMPLE => write!(f, "RST-SIMPLE"),
+			OutputFormat::ASCIIDOC   => write!(f, "ASCIIDOC"  ),
+		}
+	}
+}
+
+/// Escapes the characters that have a special meaning in HTML text and attribute values.
+///
+/// Terminal escape sequences are dropped.
+///
+/// # Arguments
+///
+/// * `text` - The raw cell content.
+///
+/// # Returns
+///
+/// A `String` safe to embed between HTML tags or inside a quoted attribute.
+pub fn escape_html(text: &str) -> String {
+	let mut escaped = String::with_capacity(text.len());
+	for c in ansi::strip(text).chars() {
+		match c {
+			'&'  => escaped.push_str("&amp;"),
+			'<'  => escaped.push_str("&lt;"),
+			'>'  => escaped.push_str("&gt;"),
+			'"'  => escaped.push_str("&quot;"),
+			'\'' => escaped.push_str("&#39;"),
+			_    => escaped.push(c),
+		}
+	}
+	escaped
+}
+
+/// Escapes the characters that have a special meaning in LaTeX text mode.
+///
+/// Terminal escape sequences are dropped.
+///
+/// # Arguments
+///
+/// * `text` - The raw cell content.
+///
+/// # Returns
+///
+/// A `String` that typesets as the original text inside a `tabular` cell.
+pub fn escape_latex(text: &str) -> String {
+	let mut escaped = String::with_capacity(text.len());
+	for c in ansi::strip(text).chars() {
+		match c {
+			'\\' => escaped.push_str("\\textbackslash{}"),
+			'&'  => escaped.push_str("\\&"),
+			'%'  => escaped.push_str("\\%"),
+			'$'  => escaped.push_str("\\$"),
+			'#'  => escaped.push_str("\\#"),
+			'_'  => escaped.push_str("\\_"),
+			'{'  => escaped.push_str("\\{"),
+			'}'  => escaped.push_str("\\}"),
+			'~'  => escaped.push_str("\\textasciitilde{}"),
+			'^'  => escaped.push_str("\\textasciicircum{}"),
+			_    => escaped.push(c),
+		}
+	}
+	escaped
+}
+
+impl TableBuilder {
+	/// Renders the table in the configured `OutputFormat`.
+	///
+	/// `OutputFormat::PLAIN` uses the `prettytable` layout produced by `build()`, every other
+	/// format is rendered from the cached `headers()`, `data()` and `numeric_columns()`.
+	/// Plain output ends with a newline.
+	///
+	/// # Returns
+	///
+	/// A `String` holding the rendered table, ready to be printed.
+	pub fn render(&mut self) -> String {
+		match self.output {
+			OutputFormat::PLAIN => self.plain(),
+			OutputFormat::HTML  => self.html(),
+			OutputFormat::LATEX => self.latex(),
+			OutputFormat::MARKDOWN   => self.markdown(),
+			OutputFormat::ORG        => self.org(),
+			OutputFormat::RST_GRID   => self.rst_grid(),
+			OutputFormat::RST_SIMPLE => self.rst_simple(),
+			OutputFormat::ASCIIDOC   => self.asciidoc(),
+		}
+	}
+
+	/// Renders the `prettytable` layout produced by `build()` as plain text.
+	///
+	/// The layout matches `Table::to_string()`, a blank line above and below the rows, but
+	/// lines are measured without escape sequences, so styled cells stay aligned. `build()`
+	/// has already padded every cell, only missing cells and lines are filled here.
+	///
+	/// # Returns
+	///
+	/// A `String` holding the table, one line per row line, each ending with a newline.
+	pub fn plain(&mut self) -> String {
+		let table = self.build();
+
+		let rows: Vec<Vec<Vec<String>>> = table.row_iter()
+			.map(|row| row.iter()
+				.map(|cell| cell.get_content().lines().map(String::from).collect())
+				.collect())
+			.collect();
+
+		let mut widths: Vec<usize> = Vec::new();
+		for row in &rows {
+			widths.resize(widths.len().max(row.len()), 0);
+			for (i, cell) in row.iter().enumerate() {
+				widths[i] = cell.iter().map(|line| ansi::width(line)).fold(widths[i], usize::max);
+			}
+		}
+
+		let blank = " ".repeat(widths.iter().sum());
+		let mut lines = vec![blank.clone()];
+
+		for row in &rows {
+			let height = row.iter().map(Vec::len).max().unwrap_or(0).max(1);
+			for l in 0..height {
+				let line: String = widths.iter()
+					.enumerate()
+					.map(|(i, &width)| match row.get(i).and_then(|cell| cell.get(l)) {
+						Some(line) => line.clone(),
+						None if i + 1 == widths.len() => String::new(),
+						None => " ".repeat(width),
+					})
+					.collect();
+				lines.push(line);
+			}
+		}
+
+		lines.push(blank);
+		lines.iter().map(|line| format!("{}\n", line)).collect()
+	}
+
+	/// Returns the header rows, the data rows and the trailing `footer_count` footer rows.
+	fn sections(&mut self) -> (Rows, Rows, Rows) {
+		let headers = self.headers().clone();
+		let mut body = self.data().clone();
+		let footers = body.split_off(body.len().saturating_sub(self.footer_count));
+		(headers, body, footers)
+	}
+
+	/// Renders the table as an HTML `<table>`.
+	///
+	/// Header rows go into `<thead>`, the last `footer_count` data rows into `<tfoot>` and
+	/// everything else into `<tbody>`. Cell content is escaped, and cells belonging to numeric
+	/// columns carry `class="num"`. With `inline_styles` every cell also gets an explicit
+	/// `text-align`, for mail clients that drop `<style>` blocks. With `standalone` the table
+	/// is wrapped in a minimal HTML document.
+	///
+	/// # Returns
+	///
+	/// A `String` containing the HTML markup.
+	pub fn html(&mut self) -> String {
+		let (headers, body, footers) = self.sections();
+		let numeric_columns = self.numeric_columns().clone();
+
+		let mut lines: Vec<String> = Vec::new();
+		lines.push("<table>".to_string());
+
+		for (section, tag, rows) in [
+			("thead", "th", &headers[..]),
+			("tbody", "td", &body[..]),
+			("tfoot", "td", &footers[..]),
+		] {
+			if rows.is_empty() {
+				continue;
+			}
+
+			lines.push(format!("  <{}>", section));
+			for row in rows {
+				lines.push("    <tr>".to_string());
+				for (i, cell) in row.iter().enumerate() {
+					let is_numeric = numeric_columns.get(i).copied().unwrap_or(false);
+					lines.push(format!(
+						"      <{tag}{}>{}</{tag}>",
+						self.html_attributes(is_numeric),
+						escape_html(cell.trim()),
+						tag = tag,
+					));
+				}
+				lines.push("    </tr>".to_string());
+			}
+			lines.push(format!("  </{}>", section));
+		}
+
+		lines.push("</table>".to_string());
+
+		let table = lines.join("\n");
+
+		if self.standalone {
+			[
+				"<!DOCTYPE html>",
+				"<html>",
+				"<head>",
+				"<meta charset=\"utf-8\">",
+				"</head>",
+				"<body>",
+				&table,
+				"</body>",
+				"</html>",
+			].join("\n")
+		} else {
+			table
+		}
+	}
+
+	/// Builds the attribute list for a single HTML cell.
+	fn html_attributes(&self, is_numeric: bool) -> String {
+		let mut attributes = String::new();
+
+		if is_numeric {
+			attributes.push_str(" class=\"num\"");
+		}
+
+		if self.inline_styles {
+			let align = if is_numeric { "right" } else { "left" };
+			attributes.push_str(&format!(" style=\"text-align: {}\"", align));
+		}
+
+		attributes
+	}
+
+	/// Renders the table as a LaTeX `tabular` environment.
+	///
+	/// The column spec is derived from `column_types()`: numeric columns are `r`, or `S` for
+	/// integer and decimal columns when `siunitx` is set, and text columns are `l`. With `booktabs` the rules are
+	/// `\toprule`, `\midrule` and `\bottomrule`, otherwise `\hline`. Header cells of `S`
+	/// columns, and body cells that are not plain numbers, such as nulls, currency amounts or
+	/// grouped digits, are wrapped in braces so siunitx does not try to parse them as numbers.
+	///
+	/// # Returns
+	///
+	/// A `String` containing the `tabular` environment.
+	pub fn latex(&mut self) -> String {
+		let (headers, body, footers) = self.sections();
+		let numeric_columns = self.numeric_columns().clone();
+		let column_types = self.column_types().clone();
+
+		let (top, mid, bottom) = if self.booktabs {
+			("\\toprule", "\\midrule", "\\bottomrule")
+		} else {
+			("\\hline", "\\hline", "\\hline")
+		};
+
+		// siunitx only reads plain numbers
+		let is_siunitx = |i: usize| self.siunitx && column_types.get(i).is_some_and(ColumnType::is_number);
+		let column_spec: String = numeric_columns.iter()
+			.enumerate()
+			.map(|(i, &is_numeric)| match (is_numeric, is_siunitx(i)) {
+				(_, true)     => "S",
+				(true, false) => "r",
+				(false, _)    => "l",
+			})
+			.collect();
+
+		let latex_row = |row: &Vec<String>, is_header: bool| -> String {
+			let cells: Vec<String> = row.iter()
+				.enumerate()
+				.map(|(i, cell)| {
+					let escaped = escape_latex(cell.trim());
+					if is_siunitx(i) && (is_header || !is_plain_number(&escaped)) {
+						format!("{{{}}}", escaped) // Protect text from siunitx
+					} else {
+						escaped
+					}
+				})
+				.collect();
+			format!("{} \\\\", cells.join(" & "))
+		};
+
+		let mut lines: Vec<String> = Vec::new();
+		lines.push(format!("\\begin{{tabular}}{{{}}}", column_spec));
+		lines.push(top.to_string());
+
+		if !headers.is_empty() {
+			lines.extend(headers.iter().map(|row| latex_row(row, true)));
+			lines.push(mid.to_string());
+		}
+
+		lines.extend(body.iter().map(|row| latex_row(row, false)));
+
+		if !footers.is_empty() {
+			lines.push(mid.to_string());
+			lines.extend(footers.iter().map(|row| latex_row(row, false)));
+		}
+
+		lines.push(bottom.to_string());
+		lines.push("\\end{tabular}".to_string());
+
+		lines.join("\n")
+	}
+
+	/// Renders the table as an Org-mode table.
+	///
+	/// Header rows and footer rows are separated from the data by `|---+---|` rules.
+	/// Pipes in cells are written as the `\vert{}` entity.
+	///
+	/// # Returns
+	///
+	/// A `String` containing the Org table.
+	pub fn org(&mut self) -> String {
+		let layout = self.text_layout(false, escape_org);
+		let rule = format!("|{}|", layout.widths.iter()
+			.map(|&width| "-".repeat(width + 2))
+			.collect::<Vec<_>>()
+			.join("+"));
+
+		let mut lines: Vec<String> = Vec::new();
+
+		if !layout.headers.is_empty() {
+			lines.extend(layout.piped_lines(&layout.headers));
+			lines.push(rule.clone());
+		}
+
+		lines.extend(layout.piped_lines(&layout.body));
+
+		if !layout.footers.is_empty() {
+			lines.push(rule);
+			lines.extend(layout.piped_lines(&layout.footers));
+		}
+
+		lines.join("\n")
+	}
+
+	/// Renders the table as a Markdown pipe table.
+	///
+	/// The delimiter row carries `:` markers so numeric columns stay right-aligned when the
+	/// Markdown is rendered. Markdown has no footer section, so footer rows are plain rows.
+	///
+	/// # Returns
+	///
+	/// A `String` containing the Markdown table.
+	pub fn markdown(&mut self) -> String {
+		let layout = self.text_layout(false, escape_pipes);
+		let delimiter = format!("|{}|", layout.widths.iter()
+			.zip(layout.numeric_columns.iter())
+			.map(|(&width, &is_numeric)| if is_numeric {
+				format!("{}:", "-".repeat(width + 1))
+			} else {
+				format!(":{}", "-".repeat(width + 1))
+			})
+			.collect::<Vec<_>>()
+			.join("|"));
+
+		// Markdown requires a header row, use an empty one if the input has none
+		let headers = if layout.headers.is_empty() {
+			vec![vec![vec![String::new()]; layout.widths.len()]]
+		} else {
+			layout.headers.clone()
+		};
+
+		let mut lines: Vec<String> = Vec::new();
+		lines.extend(layout.piped_lines(&headers));
+		lines.push(delimiter);
+		lines.extend(layout.piped_lines(&layout.body));
+		lines.extend(layout.piped_lines(&layout.footers));
+
+		lines.join("\n")
+	}
+
+	/// Renders the table as a reStructuredText grid table.
+	///
+	/// Every row is enclosed in `+---+` borders and the header is closed with a `+===+`
+	/// border. With `Frame::WRAP` cells keep their wrapped lines, so a row can span several
+	/// lines of output.
+	///
+	/// # Returns
+	///
+	/// A `String` containing the grid table.
+	pub fn rst_grid(&mut self) -> String {
+		let layout = self.text_layout(true, |text| text.to_string());
+		let border = |c: char| format!("+{}+", layout.widths.iter()
+			.map(|&width| c.to_string().repeat(width + 2))
+			.collect::<Vec<_>>()
+			.join("+"));
+
+		let mut lines: Vec<String> = vec![border('-')];
+
+		for row in &layout.headers {
+			lines.extend(layout.piped_lines(std::slice::from_ref(row)));
+			lines.push(border('-'));
+		}
+
+		// The last header border marks the end of the header
+		if !layout.headers.is_empty() {
+			lines.pop();
+			lines.push(border('='));
+		}
+
+		for row in layout.body.iter().chain(layout.footers.iter()) {
+			lines.extend(layout.piped_lines(std::slice::from_ref(row)));
+			lines.push(border('-'));
+		}
+
+		lines.join("\n")
+	}
+
+	/// Renders the table as a reStructuredText simple table.
+	///
+	/// Columns are separated by two spaces and framed by `===` rules above and below the
+	/// table and under the header. A blank first column would continue the previous row, so
+	/// empty first cells are written as an escaped space, `\ `.
+	///
+	/// # Returns
+	///
+	/// A `String` containing the simple table.
+	pub fn rst_simple(&mut self) -> String {
+		let mut layout = self.text_layout(false, |text| text.to_string());
+
+		let mut escaped = false;
+		for line in layout.headers.iter_mut()
+			.chain(layout.body.iter_mut())
+			.chain(layout.footers.iter_mut())
+			.filter_map(|row| row.first_mut()?.first_mut())
+			.filter(|line| line.is_empty())
+		{
+			*line = "\\ ".to_string();
+			escaped = true;
+		}
+		if escaped {
+			layout.widths[0] = layout.widths[0].max(2);
+		}
+
+		let rule = layout.widths.iter()
+			.map(|&width| "=".repeat(width))
+			.collect::<Vec<_>>()
+			.join("  ");
+
+		let mut lines: Vec<String> = vec![rule.clone()];
+
+		if !layout.headers.is_empty() {
+			lines.extend(layout.joined_lines(&layout.headers, "  "));
+			lines.push(rule.clone());
+		}
+
+		lines.extend(layout.joined_lines(&layout.body, "  "));
+		lines.extend(layout.joined_lines(&layout.footers, "  "));
+		lines.push(rule);
+
+		// Keep the space of an escaped space at the end of a line
+		lines.iter()
+			.map(|line| match line.trim_end() {
+				trimmed if trimmed.ends_with('\\') => &line[..line.len().min(trimmed.len() + 1)],
+				trimmed => trimmed,
+			})
+			.collect::<Vec<_>>()
+			.join("\n")
+	}
+
+	/// Renders the table as an AsciiDoc table.
+	///
+	/// The `cols` attribute aligns numeric columns to the right, and the `header`/`footer`
+	/// options mark the first row and the last row when the table has them. AsciiDoc only
+	/// knows a single header and footer row, so extra header rows are plain rows.
+	///
+	/// # Returns
+	///
+	/// A `String` containing the AsciiDoc table.
+	pub fn asciidoc(&mut self) -> String {
+		let layout = self.text_layout(false, escape_pipes);
+
+		let cols = layout.numeric_columns.iter()
+			.map(|&is_numeric| if is_numeric { ">" } else { "<" })
+			.collect::<Vec<_>>()
+			.join(",");
+
+		let mut options: Vec<&str> = Vec::new();
+		if !layout.headers.is_empty() {
+			options.push("header");
+		}
+		if !layout.footers.is_empty() {
+			options.push("footer");
+		}
+
+		let mut lines: Vec<String> = Vec::new();
+		if options.is_empty() {
+			lines.push(format!("[cols=\"{}\"]", cols));
+		} else {
+			lines.push(format!("[cols=\"{}\",options=\"{}\"]", cols, options.join(",")));
+		}
+		lines.push("|===".to_string());
+
+		for row in layout.headers.iter()
+			.chain(layout.body.iter())
+			.chain(layout.footers.iter())
+		{
+			let line = layout.joined_lines(std::slice::from_ref(row), " | ").join("\n");
+			lines.push(format!("| {}", line).trim_end().to_string());
+		}
+
+		lines.push("|===".to_string());
+
+		lines.join("\n")
+	}
+
+	/// Fits the headers and data into `column_widths()` for the plain text markup formats.
+	///
+	/// Terminal escape sequences are dropped, as markup has no use for them. Each cell is
+	/// truncated or wrapped according to `frame`, then escaped, so escapes never count against
+	/// the column width nor get cut in half. Numeric cells are
+	/// never truncated, matching `build()`. With `multiline` unset, `Frame::WRAP` keeps cells
+	/// whole on a single line since the format has no way to continue a cell. Rows shorter than
+	/// `column_count()` are completed with empty cells, and the final width of every column is
+	/// widened to the widest fitted line so the markup stays aligned.
+	fn text_layout(&mut self, multiline: bool, escape: fn(&str) -> String) -> TextLayout {
+		let column_count = self.column_count();
+		let column_widths = self.column_widths().clone();
+		let (headers, body, footers) = self.sections();
+		let numeric_columns = self.numeric_columns().clone();
+
+		let frame = match self.frame {
+			Frame::WRAP if !multiline => Frame::NONE,
+			frame => frame,
+		};
+
+		let fit_rows = |rows: &[Vec<String>]| -> Vec<Vec<Vec<String>>> {
+			rows.iter()
+				.map(|row| (0..column_count)
+					.map(|i| {
+						let cell = ansi::strip(row.get(i).map(|s| s.trim()).unwrap_or(""));
+						let is_numeric = numeric_columns.get(i).copied().unwrap_or(false);
+						let width = column_widths.get(i).copied().unwrap_or(0);
+
+						let mut formatter = TextFormatter::new(Some(cell.clone()))
+							.set_width(width)
+							.set_frame(frame)
+							.set_no_ellipsis(self.no_ellipsis)
+							.set_ellipsis(self.ellipsis.clone())
+							.set_max_lines(self.max_lines)
+							.clone();
+
+						let fitted = match frame {
+							_ if is_numeric || ansi::width(&cell) <= width => cell,
+							Frame::TRUNCATE | Frame::CHOP | Frame::MIDDLE | Frame::PATH | Frame::URL => formatter.truncate(),
+							Frame::WRAP => formatter.wrap(),
+							Frame::NONE => cell,
+						};
+
+						// Escaped once it is fitted
+						let lines: Vec<String> = fitted.lines()
+							.map(|line| escape(line.trim()))
+							.collect();
+
+						if lines.is_empty() { vec![String::new()] } else { lines }
+					})
+					.collect())
+				.collect()
+		};
+
+		let headers = fit_rows(&headers);
+		let body = fit_rows(&body);
+		let footers = fit_rows(&footers);
+
+		// Widen each column to its widest fitted line
+		let mut widths = vec![0; column_count];
+		for row in headers.iter().chain(body.iter()).chain(footers.iter()) {
+			for (i, cell) in row.iter().enumerate() {
+				for line in cell {
+					widths[i] = widths[i].max(ansi::width(line));
+				}
+			}
+		}
+
+		TextLayout {
+			headers,
+			body,
+			footers,
+			widths,
+			numeric_columns: (0..column_count)
+				.map(|i| numeric_columns.get(i).copied().unwrap_or(false))
+				.collect(),
+		}
+	}
+}
+
+/// Checks that `text` is a number siunitx reads as it is: an optional sign, then digits with
+/// an optional decimal point.
+fn is_plain_number(text: &str) -> bool {
+	let digits = text.strip_prefix(['-', '+']).unwrap_or(text);
+	let (integer, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+	!(integer.is_empty() && fraction.is_empty())
+		&& integer.chars().chain(fraction.chars()).all(|c| c.is_ascii_digit())
+}
+
+/// Escapes `|` as the Org entity `\vert{}`, since Org has no backslash escape for it.
+fn escape_org(text: &str) -> String {
+	text.replace('|', "\\vert{}")
+}
+
+/// Rows of cells, as returned by `headers()` and `data()`.
+type Rows = Vec<Vec<String>>;
+
+/// Escapes `|` so it is not mistaken for a column separator.
+fn escape_pipes(text: &str) -> String {
+	text.replace('|', "\\|")
+}
+
+/// Cells fitted into their columns, ready to be laid out as plain text markup.
+///
+/// Every row holds one entry per column, and every cell holds one or more lines.
+struct TextLayout {
+	headers: Vec<Vec<Vec<String>>>,
+	body: Vec<Vec<Vec<String>>>,
+	footers: Vec<Vec<Vec<String>>>,
+	widths: Vec<usize>,
+	numeric_columns: Vec<bool>,
+}
+
+impl TextLayout {
+	/// Pads a single line of a cell to the column width, right-aligning numeric columns.
+	fn pad(&self, line: &str, column: usize) -> String {
+		let padding = " ".repeat(self.widths[column].saturating_sub(ansi::width(line)));
+		if self.numeric_columns[column] {
+			format!("{}{}", padding, line)
+		} else {
+			format!("{}{}", line, padding)
+		}
+	}
+
+	/// Lays out `rows` with the cells joined by `separator`.
+	///
+	/// A row with multi-line cells produces one output line per cell line, with shorter cells
+	/// padded by blank lines.
+	fn joined_lines(&self, rows: &[Vec<Vec<String>>], separator: &str) -> Vec<String> {
+		let mut lines: Vec<String> = Vec::new();
+
+		for row in rows {
+			let height = row.iter().map(Vec::len).max().unwrap_or(1);
+			for n in 0..height {
+				lines.push(row.iter()
+					.enumerate()
+					.map(|(i, cell)| self.pad(cell.get(n).map(String::as_str).unwrap_or(""), i))
+					.collect::<Vec<_>>()
+					.join(separator));
+			}
+		}
+
+		lines
+	}
+
+	/// Lays out `rows` as `| a | b |` lines.
+	fn piped_lines(&self, rows: &[Vec<Vec<String>>]) -> Vec<String> {
+		self.joined_lines(rows, " | ")
+			.into_iter()
+			.map(|line| format!("| {} |", line))
+			.collect()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn table(input: &str) -> TableBuilder {
+		TableBuilder::new(Some(input.to_string()))
+	}
+
+	/// Renders a small table with a footer row and cells that need escaping.
+	fn render(output: OutputFormat) -> String {
+		table("item,qty\n<a & b>,2\nc|d,10\ntotal,12")
+			.set_ifs(",".to_string())
+			.set_footer_count(1)
+			.set_output(output)
+			.render()
+	}
+
+	#[test]
+	fn html_escapes_special_characters() {
+		assert_eq!(escape_html("<a href=\"x\">Tom & Jerry's</a>"), "&lt;a href=&quot;x&quot;&gt;Tom &amp; Jerry&#39;s&lt;/a&gt;");
+		assert_eq!(escape_html("\x1b[31mred\x1b[0m"), "red");
+	}
+
+	#[test]
+	fn sections_split_off_footer_rows() {
+		let mut builder = table("a b\n1 2\n3 4\n5 6");
+		builder.set_footer_count(2);
+		let (headers, body, footers) = builder.sections();
+		assert_eq!(headers, [["a", "b"]]);
+		assert_eq!(body, [["1", "2"]]);
+		assert_eq!(footers, [["3", "4"], ["5", "6"]]);
+
+		builder.set_footer_count(5);
+		let (_, body, footers) = builder.sections();
+		assert!(body.is_empty());
+		assert_eq!(footers.len(), 3);
+	}
+
+	#[test]
+	fn html_has_head_body_and_foot() {
+		assert_eq!(render(OutputFormat::HTML), [
+			"<table>",
+			"  <thead>",
+			"    <tr>",
+			"      <th>item</th>",
+			"      <th class=\"num\">qty</th>",
+			"    </tr>",
+			"  </thead>",
+			"  <tbody>",
+			"    <tr>",
+			"      <td>&lt;a &amp; b&gt;</td>",
+			"      <td class=\"num\">2</td>",
+			"    </tr>",
+			"    <tr>",
+			"      <td>c|d</td>",
+			"      <td class=\"num\">10</td>",
+			"    </tr>",
+			"  </tbody>",
+			"  <tfoot>",
+			"    <tr>",
+			"      <td>total</td>",
+			"      <td class=\"num\">12</td>",
+			"    </tr>",
+			"  </tfoot>",
+			"</table>",
+		].join("\n"));
+	}
+
+	#[test]
+	fn html_inline_styles_and_standalone_document() {
+		let html = table("item qty\na 1")
+			.set_output(OutputFormat::HTML)
+			.set_inline_styles(true)
+			.set_standalone(true)
+			.render();
+		assert_eq!(html, [
+			"<!DOCTYPE html>",
+			"<html>",
+			"<head>",
+			"<meta charset=\"utf-8\">",
+			"</head>",
+			"<body>",
+			"<table>",
+			"  <thead>",
+			"    <tr>",
+			"      <th style=\"text-align: left\">item</th>",
+			"      <th class=\"num\" style=\"text-align: right\">qty</th>",
+			"    </tr>",
+			"  </thead>",
+			"  <tbody>",
+			"    <tr>",
+			"      <td style=\"text-align: left\">a</td>",
+			"      <td class=\"num\" style=\"text-align: right\">1</td>",
+			"    </tr>",
+			"  </tbody>",
+			"</table>",
+			"</body>",
+			"</html>",
+		].join("\n"));
+	}
+
+	#[test]
+	fn latex_escapes_special_characters() {
+		assert_eq!(escape_latex("50% of $5 & #1_{a}"), "50\\% of \\$5 \\& \\#1\\_\\{a\\}");
+		assert_eq!(escape_latex("~^\\"), "\\textasciitilde{}\\textasciicircum{}\\textbackslash{}");
+		assert_eq!(escape_latex("\x1b[1mbold\x1b[0m"), "bold");
+	}
+
+	#[test]
+	fn latex_rules_the_header_and_footer() {
+		assert_eq!(render(OutputFormat::LATEX), [
+			"\\begin{tabular}{lr}",
+			"\\hline",
+			"item & qty \\\\",
+			"\\hline",
+			"<a \\& b> & 2 \\\\",
+			"c|d & 10 \\\\",
+			"\\hline",
+			"total & 12 \\\\",
+			"\\hline",
+			"\\end{tabular}",
+		].join("\n"));
+	}
+
+	#[test]
+	fn latex_booktabs_rules() {
+		let latex = table("item qty\na 1")
+			.set_output(OutputFormat::LATEX)
+			.set_booktabs(true)
+			.render();
+		assert_eq!(latex, "\\begin{tabular}{lr}\n\\toprule\nitem & qty \\\\\n\\midrule\na & 1 \\\\\n\\bottomrule\n\\end{tabular}");
+	}
+
+	#[test]
+	fn plain_numbers_for_siunitx() {
+		assert!(is_plain_number("-1234.5"));
+		assert!(is_plain_number("+.5"));
+		assert!(is_plain_number("7"));
+		assert!(!is_plain_number("1,234"));
+		assert!(!is_plain_number("\\$5"));
+		assert!(!is_plain_number("."));
+		assert!(!is_plain_number(""));
+	}
+
+	#[test]
+	fn siunitx_braces_cells_that_are_not_plain_numbers() {
+		let latex = table("name amount\na 1234567.5\nb NULL\nc -3")
+			.set_output(OutputFormat::LATEX)
+			.set_siunitx(true)
+			.set_use_thousand_separator(true)
+			.render();
+		assert_eq!(latex, [
+			"\\begin{tabular}{lS}",
+			"\\hline",
+			"name & {amount} \\\\",
+			"\\hline",
+			"a & {1,234,567.5} \\\\",
+			"b & {NULL} \\\\",
+			"c & -3 \\\\",
+			"\\hline",
+			"\\end{tabular}",
+		].join("\n"));
+	}
+
+	#[test]
+	fn pipes_are_escaped() {
+		assert_eq!(escape_pipes("a|b||c"), "a\\|b\\|\\|c");
+		assert_eq!(escape_org("a|b"), "a\\vert{}b");
+	}
+
+	#[test]
+	fn markdown_aligns_numeric_columns_right() {
+		assert_eq!(render(OutputFormat::MARKDOWN), [
+			"| item    | qty |",
+			"|:--------|----:|",
+			"| <a & b> |   2 |",
+			"| c\\|d    |  10 |",
+			"| total   |  12 |",
+		].join("\n"));
+	}
+
+	#[test]
+	fn markdown_without_headers_has_an_empty_header_row() {
+		let markdown = table("a 1\nb 2")
+			.set_header_index(0)
+			.set_output(OutputFormat::MARKDOWN)
+			.render();
+		assert_eq!(markdown, "|   |   |\n|:--|--:|\n| a | 1 |\n| b | 2 |");
+	}
+
+	#[test]
+	fn org_rules_the_header_and_footer() {
+		assert_eq!(render(OutputFormat::ORG), [
+			"| item      | qty |",
+			"|-----------+-----|",
+			"| <a & b>   |   2 |",
+			"| c\\vert{}d |  10 |",
+			"|-----------+-----|",
+			"| total     |  12 |",
+		].join("\n"));
+	}
+
+	#[test]
+	fn rst_grid_borders_every_row() {
+		assert_eq!(render(OutputFormat::RST_GRID), [
+			"+---------+-----+",
+			"| item    | qty |",
+			"+=========+=====+",
+			"| <a & b> |   2 |",
+			"+---------+-----+",
+			"| c|d     |  10 |",
+			"+---------+-----+",
+			"| total   |  12 |",
+			"+---------+-----+",
+		].join("\n"));
+	}
+
+	#[test]
+	fn rst_grid_keeps_wrapped_lines() {
+		let rst = table("name,note\nalpha,one two three")
+			.set_ifs(",".to_string())
+			.set_max_cell_width(7)
+			.set_frame(Frame::WRAP)
+			.set_output(OutputFormat::RST_GRID)
+			.render();
+		assert_eq!(rst, [
+			"+-------+---------+",
+			"| name  | note    |",
+			"+=======+=========+",
+			"| alpha | one two |",
+			"|       | three   |",
+			"+-------+---------+",
+		].join("\n"));
+	}
+
+	#[test]
+	fn rst_simple_frames_the_table_with_rules() {
+		assert_eq!(render(OutputFormat::RST_SIMPLE), [
+			"=======  ===",
+			"item     qty",
+			"=======  ===",
+			"<a & b>    2",
+			"c|d       10",
+			"total     12",
+			"=======  ===",
+		].join("\n"));
+	}
+
+	#[test]
+	fn asciidoc_marks_header_and_footer() {
+		assert_eq!(render(OutputFormat::ASCIIDOC), [
+			"[cols=\"<,>\",options=\"header,footer\"]",
+			"|===",
+			"| item    | qty",
+			"| <a & b> |   2",
+			"| c\\|d    |  10",
+			"| total   |  12",
+			"|===",
+		].join("\n"));
+	}
+
+	#[test]
+	fn rst_simple_escapes_empty_first_cells() {
+		let rst = table("a,b\n,1\nx,\n,")
+			.set_ifs(",".to_string())
+			.set_output(OutputFormat::RST_SIMPLE)
+			.render();
+		assert_eq!(rst, [
+			"==  =",
+			"a   b",
+			"==  =",
+			"\\   1",
+			"x",
+			"\\ ",
+			"==  =",
+		].join("\n"));
+	}
+
+	#[test]
+	fn markup_drops_terminal_escapes() {
+		let markdown = table("name link\n\x1b[1mbold\x1b[0m \x1b]8;;https://example.com\x1b\\site\x1b]8;;\x1b\\")
+			.set_output(OutputFormat::MARKDOWN)
+			.render();
+		assert_eq!(markdown, "| name | link |\n|:-----|:-----|\n| bold | site |");
+	}
+}
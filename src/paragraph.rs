@@ -0,0 +1,186 @@
+use crate::ansi;
+
+/// A part of a text that is reflowed on its own, or kept as it is.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Block<'a> {
+	/// A line kept as it is: a blank line between paragraphs, or a line of a fenced code block.
+	Verbatim(&'a str),
+
+	/// A paragraph or list item, with its lines joined into one.
+	Paragraph {
+		/// Leading whitespace of the first line.
+		indent: &'a str,
+
+		/// List marker of a list item, with the spaces after it: `- `, `1. `.
+		marker: &'a str,
+
+		/// Words of every line of the paragraph, joined by single spaces.
+		text: String,
+	},
+}
+
+impl Block<'_> {
+	/// Returns the prefix of the first line of a paragraph, and the hanging indent of the
+	/// following lines, aligned with the text after the list marker.
+	pub fn prefixes(&self) -> (String, String) {
+		match self {
+			Block::Verbatim(_) => (String::new(), String::new()),
+			Block::Paragraph { indent, marker, .. } => (
+				format!("{}{}", indent, marker),
+				format!("{}{}", indent, " ".repeat(ansi::width(marker))),
+			),
+		}
+	}
+}
+
+/// Splits `text` into paragraphs for reflowing.
+///
+/// Paragraphs are separated by blank lines and start at list items, marked with `-`, `*`, `+`,
+/// `1.` or `1)`. Lines that follow are joined to the paragraph whatever their indentation.
+/// Fenced code blocks, from a line starting with ```` ``` ```` or `~~~` to the next one, are
+/// kept as they are.
+pub fn blocks(text: &str) -> Vec<Block<'_>> {
+	let mut blocks = Vec::new();
+	let mut fence: Option<&str> = None;
+
+	for line in text.lines() {
+		let trimmed = line.trim_start();
+
+		// Lines of a code block, and the fences around it, are kept as they are
+		if let Some(open) = fence {
+			if trimmed.starts_with(open) {
+				fence = None;
+			}
+			blocks.push(Block::Verbatim(line));
+			continue;
+		}
+		if let Some(open) = ["```", "~~~"].into_iter().find(|open| trimmed.starts_with(open)) {
+			fence = Some(open);
+			blocks.push(Block::Verbatim(line));
+			continue;
+		}
+
+		if trimmed.is_empty() {
+			blocks.push(Block::Verbatim(""));
+			continue;
+		}
+
+		let indent = &line[..line.len() - trimmed.len()];
+		let marker = list_marker(trimmed);
+		let words = trimmed[marker.len()..].trim();
+
+		match blocks.last_mut() {
+			Some(Block::Paragraph { text, .. }) if marker.is_empty() => {
+				text.push(' ');
+				text.push_str(words);
+			},
+			_ => blocks.push(Block::Paragraph { indent, marker, text: words.to_string() }),
+		}
+	}
+
+	blocks
+}
+
+/// Returns the list marker at the start of `line` with the spaces after it, or nothing when
+/// the line is not a list item.
+fn list_marker(line: &str) -> &str {
+	let digits = line.len() - line.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+	let marker = match line[digits..].chars().next() {
+		Some('-' | '*' | '+') if digits == 0 => 1,
+		Some('.' | ')') if digits > 0 => digits + 1,
+		_ => return "",
+	};
+
+	let rest = &line[marker..];
+	let spaces = rest.len() - rest.trim_start_matches(' ').len();
+	if spaces == 0 || spaces == rest.len() {
+		return "";
+	}
+	&line[..marker + spaces]
+}
+
+/// Comment and quote markers, longest first so that `///` is not read as `//`.
+///
+/// The `*` of block comments is only a marker between `/*` and `*/`, see [`comments`].
+const COMMENT_MARKERS: [&str; 5] = ["///", "//!", "//", "#", ">"];
+
+/// Returns the comment prefix of `line`: its indentation, a comment marker and the space
+/// after it, such as `// ` or ` * `. Quote markers may repeat for nested quotes: `> > `.
+///
+/// Lines without a marker have no prefix, their indentation is left to [`blocks`].
+pub fn comment_prefix(line: &str) -> &str {
+	let rest = line.trim_start();
+	let Some(marker) = COMMENT_MARKERS.iter().find(|marker| rest.starts_with(*marker)) else {
+		return "";
+	};
+
+	let mut end = line.len() - rest.len() + marker.len();
+	if *marker == ">" {
+		// Nested quotes, `>>` or `> >`
+		while let Some(next) = line[end..].trim_start_matches(' ').strip_prefix('>') {
+			end = line.len() - next.len();
+		}
+	}
+	if line[end..].starts_with(' ') {
+		end += 1;
+	}
+
+	&line[..end]
+}
+
+/// Returns the prefix of a line of a `/* ... */` block comment: its indentation and the `*`
+/// with the space after it, such as ` * `.
+///
+/// The lines that open and close the block are prefixes as a whole, so they are kept as they
+/// are. Lines without a `*` have no prefix.
+fn block_comment_prefix(line: &str) -> &str {
+	let rest = line.trim_start();
+	if rest.starts_with("/*") || rest.contains("*/") {
+		return line.trim_end();
+	}
+
+	match rest.strip_prefix('*') {
+		Some("") => line,
+		Some(text) if text.starts_with(' ') => &line[..line.len() - text.len() + 1],
+		_ => "",
+	}
+}
+
+/// Splits `text` into runs of lines with the same comment prefix, returning the prefix of each
+/// run with its lines stripped of it.
+///
+/// Lines of a run may differ in the spaces around the markers, as a bare `//` between
+/// paragraphs of a comment or `>>` among `> >` quotes do, and the run takes the longest prefix.
+/// From a line starting with `/*` to the next line with `*/`, the prefixes are those of a
+/// block comment instead, see [`block_comment_prefix`].
+pub fn comments(text: &str) -> Vec<(&str, String)> {
+	let mut runs: Vec<(&str, String)> = Vec::new();
+	let mut block = false;
+
+	for line in text.lines() {
+		block = block || line.trim_start().starts_with("/*");
+		let prefix = if block { block_comment_prefix(line) } else { comment_prefix(line) };
+		let body = &line[prefix.len()..];
+		block = block && !line.contains("*/");
+
+		match runs.last_mut() {
+			Some((run, lines)) if marker_key(run) == marker_key(prefix) => {
+				if prefix.len() > run.len() {
+					*run = prefix;
+				}
+				lines.push('\n');
+				lines.push_str(body);
+			},
+			_ => runs.push((prefix, body.to_string())),
+		}
+	}
+
+	runs
+}
+
+/// Returns the indentation and markers of a comment prefix without the spaces between them.
+fn marker_key(prefix: &str) -> String {
+	let markers = prefix.trim_start();
+	let indent = &prefix[..prefix.len() - markers.len()];
+	format!("{}{}", indent, markers.replace(' ', ""))
+}
@@ -0,0 +1,430 @@
+use clap::ValueEnum;
+use std::fmt;
+use std::str::FromStr;
+use unicode_width::UnicodeWidthChar;
+
+#[derive(Copy, Clone, Default, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+pub enum InputFormat {
+	/// Fields separated by the input field separator.
+	#[default]
+	DELIMITED,
+
+	/// A Markdown pipe table.
+	MARKDOWN,
+
+	/// An Org-mode table.
+	ORG,
+
+	/// An ASCII-art box table drawn with `+---+` borders.
+	BOX,
+
+	/// Fixed-width columns, where values may contain spaces.
+	FIXED,
+}
+
+impl FromStr for InputFormat {
+	type Err = String;
+
+	fn from_str(text: &str) -> Result<InputFormat, Self::Err> {
+		match text.to_uppercase().as_str() {
+			"DELIMITED" => Ok(InputFormat::DELIMITED),
+			"MARKDOWN"  => Ok(InputFormat::MARKDOWN),
+			"ORG"       => Ok(InputFormat::ORG),
+			"BOX"       => Ok(InputFormat::BOX),
+			"FIXED"     => Ok(InputFormat::FIXED),
+			_ => Err(format!("Invalid input format: {}", text)),
+		}
+	}
+}
+
+impl fmt::Display for InputFormat {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			InputFormat::DELIMITED => write!(f, "DELIMITED"),
+			InputFormat::MARKDOWN  => write!(f, "MARKDOWN" ),
+			InputFormat::ORG       => write!(f, "ORG"      ),
+			InputFormat::BOX       => write!(f, "BOX"      ),
+			InputFormat::FIXED     => write!(f, "FIXED"    ),
+		}
+	}
+}
+
+/// Rows read from the input, along with the header rows the input format declared.
+pub struct ParsedTable {
+	/// The rows of the table, borders and separator rows removed.
+	pub rows: Vec<Vec<String>>,
+
+	/// Number of leading rows the input marks as header rows.
+	///
+	/// `None` when the input format carries no header information, in which case the
+	/// configured header rows apply.
+	pub header_count: Option<usize>,
+
+	/// The 1-based input line every row starts on.
+	pub lines: Vec<usize>,
+}
+
+/// Splits `text` into rows according to `format`.
+///
+/// # Arguments
+///
+/// * `text` - The input text. Blank lines are skipped, and rows keep the line numbers of `text`.
+/// * `format` - The layout of the input.
+/// * `ifs` - The input field separator, used by `InputFormat::DELIMITED`.
+/// * `widths` - The column widths used by `InputFormat::FIXED`, detected when empty.
+///
+/// # Returns
+///
+/// A `ParsedTable` with the rows found in the input.
+pub fn parse(text: &str, format: InputFormat, ifs: &str, widths: &[usize]) -> ParsedTable {
+	match format {
+		InputFormat::DELIMITED => {
+			let (lines, rows) = numbered_lines(text)
+				.map(|(number, line)| (number, line.trim().split(ifs).map(String::from).collect()))
+				.unzip();
+			ParsedTable { rows, header_count: None, lines }
+		},
+		InputFormat::MARKDOWN | InputFormat::ORG => parse_pipe_table(text),
+		InputFormat::BOX => parse_box_table(text),
+		InputFormat::FIXED => parse_fixed(text, widths),
+	}
+}
+
+/// Reads fixed-width columns.
+///
+/// Tabs are expanded to 8-column stops and every line is measured in display columns. With
+/// explicit `widths` each line is sliced at those widths, and the last column takes the rest
+/// of the line. Otherwise the column boundaries are the gutters: runs of display columns that
+/// are blank on every line. Values are trimmed, so single spaces inside a value never split it
+/// as long as some line has a character in that position.
+fn parse_fixed(text: &str, widths: &[usize]) -> ParsedTable {
+	let (numbers, lines): (Vec<usize>, Vec<Vec<(usize, char)>>) = numbered_lines(text)
+		.map(|(number, line)| (number, display_columns(line)))
+		.unzip();
+
+	let spans: Vec<(usize, usize)> = if widths.is_empty() {
+		detect_fixed_spans(&lines)
+	} else {
+		let mut start = 0;
+		widths.iter()
+			.enumerate()
+			.map(|(i, &width)| {
+				let span = if i + 1 == widths.len() {
+					(start, usize::MAX)
+				} else {
+					(start, start + width)
+				};
+				start += width;
+				span
+			})
+			.collect()
+	};
+
+	let rows = lines.iter()
+		.map(|line| spans.iter()
+			.map(|&(start, end)| line.iter()
+				.filter(|(column, _)| start <= *column && *column < end)
+				.map(|(_, c)| *c)
+				.collect::<String>()
+				.trim()
+				.to_string())
+			.collect())
+		.collect();
+
+	ParsedTable { rows, header_count: None, lines: numbers }
+}
+
+/// Finds the column spans of fixed-width lines, as `(start, end)` display columns.
+///
+/// A display column is blank when no line puts a visible character there, and every run of
+/// non-blank display columns is a span. A span behind a single blank display column is merged
+/// into the span before it when it looks like the rest of a value: it is blank on some line,
+/// and wherever it holds text the span before it runs right up to the gap, as `Alice Smith`
+/// does under a short `NAME` header. The last span is open-ended.
+fn detect_fixed_spans(lines: &[Vec<(usize, char)>]) -> Vec<(usize, usize)> {
+	// Visible display columns of every line
+	let occupied: Vec<Vec<bool>> = lines.iter()
+		.map(|line| {
+			let mut occupied: Vec<bool> = Vec::new();
+			for &(column, c) in line {
+				if c.is_whitespace() {
+					continue;
+				}
+				let width = c.width().unwrap_or(0).max(1);
+				if occupied.len() < column + width {
+					occupied.resize(column + width, false);
+				}
+				occupied[column..column + width].iter_mut().for_each(|o| *o = true);
+			}
+			occupied
+		})
+		.collect();
+
+	let width = occupied.iter().map(Vec::len).max().unwrap_or(0);
+	let is_set = |line: &Vec<bool>, column: usize| line.get(column).copied().unwrap_or(false);
+
+	let mut spans: Vec<(usize, usize)> = Vec::new();
+	let mut start: Option<usize> = None;
+	for column in 0..=width {
+		let used = column < width && occupied.iter().any(|line| is_set(line, column));
+		match (used, start) {
+			(true, None) => start = Some(column),
+			(false, Some(s)) => {
+				spans.push((s, column));
+				start = None;
+			},
+			_ => {},
+		}
+	}
+
+	// Merge spans that continue the value on their left
+	let mut merged: Vec<(usize, usize)> = Vec::new();
+	for (s, e) in spans {
+		if let Some(last) = merged.last_mut() {
+			let has_text = |line: &Vec<bool>| (s..e).any(|column| is_set(line, column));
+			let continues = s == last.1 + 1
+				&& occupied.iter().any(|line| !has_text(line))
+				&& occupied.iter()
+					.filter(|line| has_text(line))
+					.all(|line| is_set(line, last.1 - 1));
+			if continues {
+				last.1 = e;
+				continue;
+			}
+		}
+		merged.push((s, e));
+	}
+
+	// The last column takes whatever follows it
+	if let Some(last) = merged.last_mut() {
+		last.1 = usize::MAX;
+	}
+
+	merged
+}
+
+/// Pairs every character of `line` with the display column it starts at, expanding tabs.
+fn display_columns(line: &str) -> Vec<(usize, char)> {
+	let mut column = 0;
+	let mut chars: Vec<(usize, char)> = Vec::new();
+
+	for c in line.chars() {
+		if c == '\t' {
+			let stop = (column / 8 + 1) * 8;
+			while column < stop {
+				chars.push((column, ' '));
+				column += 1;
+			}
+		} else {
+			chars.push((column, c));
+			column += c.width().unwrap_or(0);
+		}
+	}
+
+	chars
+}
+
+/// Reads a Markdown or Org-mode pipe table.
+///
+/// Lines made only of `|`, `+`, `-`, `:` and spaces are separator rows, the rows above the
+/// first separator row are the header. Once the header is closed, only rules whose cells are
+/// all at least three dashes, such as Org's `|---+---|`, are separators, so a row of `-` null
+/// values is read as data. Leading and trailing pipes are optional, and `\|` inside a cell
+/// is read as a literal pipe.
+fn parse_pipe_table(text: &str) -> ParsedTable {
+	let mut rows: Vec<Vec<String>> = Vec::new();
+	let mut lines: Vec<usize> = Vec::new();
+	let mut header_count: Option<usize> = None;
+
+	for (number, line) in numbered_lines(text) {
+		let line = line.trim();
+		if is_separator(line, "|+-:") && (header_count.is_none() || is_rule(line)) {
+			// The first separator row closes the header
+			if header_count.is_none() && !rows.is_empty() {
+				header_count = Some(rows.len());
+			}
+			continue;
+		}
+		rows.push(split_cells(line, &['|']));
+		lines.push(number);
+	}
+
+	ParsedTable { rows, header_count: Some(header_count.unwrap_or(0)), lines }
+}
+
+/// Reads an ASCII-art box table, such as those printed by `mysql`, `psql` or reStructuredText
+/// grid tables.
+///
+/// Border lines are made only of `+`, `-`, `=`, `|` and their box-drawing equivalents. The
+/// content lines are grouped into blocks separated by border lines. The first block is the
+/// header when a `=` border closes it or when the table has more than one block.
+///
+/// Grid tables draw a border between every two rows: their header is closed by a `=` border,
+/// as in reStructuredText, or more than two blocks follow the header. There every block is one
+/// row, and the lines of a block are joined cell by cell, so multi-line grid cells are read
+/// back into a single cell. Otherwise a block holds a run of rows, one per line, as the body
+/// and footer of `mysql` or `psql` output do.
+fn parse_box_table(text: &str) -> ParsedTable {
+	const BORDER: &str = "+-=|:─━═│┃║┌┐└┘├┤┬┴┼╞╡╪╤╧╔╗╚╝╠╣╦╩╬";
+	const DIVIDERS: [char; 3] = ['|', '│', '┃'];
+
+	// Lines of every block, with their line numbers
+	let mut blocks: Vec<Vec<(usize, Vec<String>)>> = vec![Vec::new()];
+	let mut double_border_after: Option<usize> = None;
+
+	for (number, line) in numbered_lines(text) {
+		let line = line.trim();
+		if is_separator(line, BORDER) {
+			if blocks.last().is_some_and(|block| !block.is_empty()) {
+				if double_border_after.is_none() && line.contains(['=', '═']) {
+					double_border_after = Some(blocks.len());
+				}
+				blocks.push(Vec::new());
+			}
+			continue;
+		}
+		if let Some(block) = blocks.last_mut() {
+			block.push((number, split_cells(line, &DIVIDERS)));
+		}
+	}
+
+	blocks.retain(|block| !block.is_empty());
+
+	let header_blocks = match double_border_after {
+		Some(count) => count,
+		None if blocks.len() > 1 => 1,
+		None => 0,
+	};
+
+	// Blocks after the header hold one row each when borders separate every row
+	let is_grid = double_border_after.is_some() || blocks.len() > header_blocks + 2;
+
+	if is_grid {
+		// Every block is a single row whose cells may span several lines
+		let lines = blocks.iter().map(|block| block[0].0).collect();
+		let rows: Vec<Vec<String>> = blocks.into_iter()
+			.map(|block| {
+				let width = block.iter().map(|(_, cells)| cells.len()).max().unwrap_or(0);
+				(0..width)
+					.map(|i| block.iter()
+						.filter_map(|(_, cells)| cells.get(i))
+						.map(String::as_str)
+						.filter(|part| !part.is_empty())
+						.collect::<Vec<_>>()
+						.join(" "))
+					.collect()
+			})
+			.collect();
+		ParsedTable { rows, header_count: Some(header_blocks), lines }
+	} else {
+		let header_count = blocks.iter().take(header_blocks).map(Vec::len).sum();
+		let (lines, rows) = blocks.into_iter().flatten().unzip();
+		ParsedTable { rows, header_count: Some(header_count), lines }
+	}
+}
+
+/// Pairs the lines of `text` that are not blank with their 1-based line numbers.
+fn numbered_lines(text: &str) -> impl Iterator<Item = (usize, &str)> {
+	text.lines()
+		.enumerate()
+		.filter(|(_, line)| !line.trim().is_empty())
+		.map(|(i, line)| (i + 1, line))
+}
+
+/// Checks whether `line` is a border or separator line drawn with `chars`.
+///
+/// A line holding nothing but cell dividers is an empty row, not a separator.
+fn is_separator(line: &str, chars: &str) -> bool {
+	line.chars().any(|c| !matches!(c, ' ' | '|' | '│' | '┃'))
+		&& line.chars().all(|c| c == ' ' || chars.contains(c))
+}
+
+/// Checks whether every cell of a separator line is a rule of at least three dashes, with
+/// optional alignment colons: `|---+--:|`.
+fn is_rule(line: &str) -> bool {
+	line.trim_matches('|')
+		.split(['|', '+'])
+		.map(|cell| cell.trim().trim_start_matches(':').trim_end_matches(':'))
+		.all(|cell| cell.len() >= 3 && cell.chars().all(|c| c == '-'))
+}
+
+/// Splits a table line on `dividers`, dropping the outer borders and trimming each cell.
+///
+/// A divider preceded by a backslash is kept as part of the cell.
+fn split_cells(line: &str, dividers: &[char]) -> Vec<String> {
+	let mut cells: Vec<String> = Vec::new();
+	let mut cell = String::new();
+	let mut chars = line.chars().peekable();
+
+	while let Some(c) = chars.next() {
+		if c == '\\' && chars.peek().is_some_and(|next| dividers.contains(next)) {
+			cell.push(chars.next().unwrap_or(c));
+		} else if dividers.contains(&c) {
+			cells.push(cell.trim().to_string());
+			cell.clear();
+		} else {
+			cell.push(c);
+		}
+	}
+	cells.push(cell.trim().to_string());
+
+	// Drop the empty cells left by the outer borders
+	if line.starts_with(|c| dividers.contains(&c)) {
+		cells.remove(0);
+	}
+	if line.ends_with(|c| dividers.contains(&c)) && !line.ends_with("\\|") {
+		cells.pop();
+	}
+
+	cells
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn rows(table: &ParsedTable) -> Vec<Vec<&str>> {
+		table.rows.iter().map(|row| row.iter().map(String::as_str).collect()).collect()
+	}
+
+	#[test]
+	fn pipe_rows_of_dashes_are_data() {
+		let text = "| k | n |\n|---|--:|\n| a | 1 |\n| - | - |\n|---+---|\n| s | 1 |";
+		let table = parse_pipe_table(text);
+		assert_eq!(rows(&table), vec![vec!["k", "n"], vec!["a", "1"], vec!["-", "-"], vec!["s", "1"]]);
+		assert_eq!(table.header_count, Some(1));
+	}
+
+	#[test]
+	fn mysql_footer_rows_stay_apart() {
+		let text = "+-----+---+\n| k   | n |\n+-----+---+\n| a   | 1 |\n| b   | 2 |\n+-----+---+\n| sum | 3 |\n+-----+---+";
+		let table = parse_box_table(text);
+		assert_eq!(rows(&table), vec![vec!["k", "n"], vec!["a", "1"], vec!["b", "2"], vec!["sum", "3"]]);
+		assert_eq!(table.header_count, Some(1));
+		assert_eq!(table.lines, vec![2, 4, 5, 7]);
+	}
+
+	#[test]
+	fn psql_rows_stay_apart() {
+		let text = " k | n\n---+---\n a | 1\n b | 2";
+		let table = parse_box_table(text);
+		assert_eq!(rows(&table), vec![vec!["k", "n"], vec!["a", "1"], vec!["b", "2"]]);
+		assert_eq!(table.header_count, Some(1));
+	}
+
+	#[test]
+	fn grid_cells_are_joined() {
+		let text = "+------+----------+\n| k    | note     |\n+======+==========+\n| a    | first    |\n|      | line two |\n+------+----------+";
+		let table = parse_box_table(text);
+		assert_eq!(rows(&table), vec![vec!["k", "note"], vec!["a", "first line two"]]);
+		assert_eq!(table.header_count, Some(1));
+		assert_eq!(table.lines, vec![2, 4]);
+	}
+
+	#[test]
+	fn grid_without_header_border_is_read_by_rows() {
+		let text = "+---+---+\n| k | n |\n+---+---+\n| a | 1 |\n|   | 2 |\n+---+---+\n| b | 3 |\n+---+---+\n| c | 4 |\n+---+---+";
+		let table = parse_box_table(text);
+		assert_eq!(rows(&table), vec![vec!["k", "n"], vec!["a", "1 2"], vec!["b", "3"], vec!["c", "4"]]);
+	}
+}
@@ -1,8 +1,249 @@
-use clap::Args;
+use chrono::{DateTime, FixedOffset};
+use clap::{Args, ValueEnum};
+use crate::ansi;
+use crate::datetime;
 use crate::input;
-use crate::text::{TextFormatter, Alignment, Frame, clean};
+use eyre::{eyre, Result};
+use crate::decimal::{Decimal, Rounding};
+use crate::infer::{self, ColumnType};
+use crate::output::OutputFormat;
+use crate::parse::{self, InputFormat};
+use crate::text::{TextFormatter, Alignment, CurrencyPosition, Frame, Grouping, Locale, NumberDisplay};
 pub use prettytable::{format, Cell, Row, Table};
-use unicode_width::UnicodeWidthStr;
+use std::cmp::Ordering;
+use std::fmt;
+use std::io::IsTerminal;
+use std::str::FromStr;
+
+/// A column selected by its 1-based index or by the text of its first header cell.
+#[derive(Clone)]
+pub enum ColumnRef {
+	Index(usize),
+	Name(String),
+}
+
+impl FromStr for ColumnRef {
+	type Err = String;
+
+	fn from_str(text: &str) -> Result<ColumnRef, Self::Err> {
+		let text = text.trim();
+		match text.parse::<usize>() {
+			Ok(0) => Err("Column indexes start at 1".to_string()),
+			Ok(index) => Ok(ColumnRef::Index(index)),
+			Err(_) if text.is_empty() => Err("Missing column".to_string()),
+			Err(_) => Ok(ColumnRef::Name(text.to_string())),
+		}
+	}
+}
+
+impl fmt::Display for ColumnRef {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			ColumnRef::Index(index) => write!(f, "{}", index),
+			ColumnRef::Name(name)   => write!(f, "{}", name),
+		}
+	}
+}
+
+/// Number display mode for a single column, written as `<column>:<display>`, e.g. `size:bytes`.
+#[derive(Clone)]
+pub struct ColumnDisplay {
+	pub column: ColumnRef,
+	pub display: NumberDisplay,
+}
+
+impl FromStr for ColumnDisplay {
+	type Err = String;
+
+	fn from_str(text: &str) -> Result<ColumnDisplay, Self::Err> {
+		let (column, display) = text.rsplit_once(':')
+			.ok_or_else(|| format!("Invalid column display, expected <column>:<display>: {}", text))?;
+
+		Ok(ColumnDisplay {
+			column: column.parse()?,
+			display: display.parse()?,
+		})
+	}
+}
+
+/// How a highlight rule compares a cell with its value.
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum Comparison {
+	EQ,
+	NE,
+	LT,
+	LE,
+	GT,
+	GE,
+	CONTAINS,
+}
+
+impl Comparison {
+	/// The operators in the order they are looked for, two-character ones first.
+	const OPERATORS: [(&'static str, Comparison); 7] = [
+		("!=", Comparison::NE),
+		("<=", Comparison::LE),
+		(">=", Comparison::GE),
+		("=",  Comparison::EQ),
+		("<",  Comparison::LT),
+		(">",  Comparison::GT),
+		("~",  Comparison::CONTAINS),
+	];
+}
+
+/// A rule styling the cells, or the rows, whose value matches a condition, written as
+/// `<column><operator><value>:<styles>`, e.g. `latency>500:red` or `status=FAIL:row,bold`.
+///
+/// The operators are `=`, `!=`, `<`, `<=`, `>`, `>=` and `~` (contains). The styles are the
+/// names read by `ansi::parse_style`, plus `row` to style the whole row.
+#[derive(Clone)]
+pub struct Highlight {
+	pub column: ColumnRef,
+	pub comparison: Comparison,
+	pub value: String,
+
+	/// The SGR sequence of the styles.
+	pub style: String,
+
+	/// Whether the whole row is styled, rather than the cell.
+	pub row: bool,
+}
+
+impl FromStr for Highlight {
+	type Err = String;
+
+	fn from_str(text: &str) -> Result<Highlight, Self::Err> {
+		let invalid = || format!("Invalid highlight, expected <column><operator><value>:<styles>: {}", text);
+
+		let (condition, styles) = text.rsplit_once(':').ok_or_else(invalid)?;
+		let start = condition.find(['=', '!', '<', '>', '~']).ok_or_else(invalid)?;
+		let (operator, comparison) = Comparison::OPERATORS.iter()
+			.find(|(operator, _)| condition[start..].starts_with(operator))
+			.ok_or_else(invalid)?;
+
+		let row = styles.split([',', ' ']).any(|name| name.trim().eq_ignore_ascii_case("row"));
+		let styles: Vec<&str> = styles.split([',', ' '])
+			.filter(|name| !name.trim().is_empty() && !name.trim().eq_ignore_ascii_case("row"))
+			.collect();
+
+		Ok(Highlight {
+			column: condition[..start].parse()?,
+			comparison: *comparison,
+			value: condition[start + operator.len()..].trim().to_string(),
+			style: if styles.is_empty() { String::new() } else { ansi::parse_style(&styles.join(","))? },
+			row,
+		})
+	}
+}
+
+impl Highlight {
+	/// Checks a cell of a column of type `column_type` against the rule.
+	///
+	/// Cells compare as their column type, so numbers compare by value, timestamps by time and
+	/// durations by length. Null cells only match `=` and `!=`, by their text.
+	pub fn matches(
+		&self,
+		cell: &str,
+		column_type: ColumnType,
+		nulls: &[&str],
+		number: &dyn Fn(&str) -> Option<Decimal>,
+	) -> bool {
+		let cell = ansi::strip(cell);
+		let cell = cell.trim();
+		let order = || (!nulls.contains(&cell)).then(|| column_type.compare(cell, &self.value, &[], false, number));
+
+		match self.comparison {
+			Comparison::EQ       => order().map_or(cell == self.value, Ordering::is_eq),
+			Comparison::NE       => order().map_or(cell != self.value, Ordering::is_ne),
+			Comparison::LT       => order().is_some_and(Ordering::is_lt),
+			Comparison::LE       => order().is_some_and(Ordering::is_le),
+			Comparison::GT       => order().is_some_and(Ordering::is_gt),
+			Comparison::GE       => order().is_some_and(Ordering::is_ge),
+			Comparison::CONTAINS => cell.contains(self.value.as_str()),
+		}
+	}
+}
+
+/// How rows with more or fewer fields than the header row are handled.
+#[allow(non_camel_case_types)]
+#[derive(Copy, Clone, Default, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+pub enum Ragged {
+	/// Report the line numbers of the ragged rows and fail.
+	ERROR,
+
+	/// Keep every field, padding short rows with missing values.
+	#[default]
+	PAD,
+
+	/// Drop the fields beyond the header, padding short rows with missing values.
+	TRUNCATE,
+
+	/// Join the fields beyond the header back into the last column with the input field
+	/// separator, padding short rows with missing values.
+	MERGE_TAIL,
+}
+
+impl FromStr for Ragged {
+	type Err = String;
+
+	fn from_str(text: &str) -> Result<Ragged, Self::Err> {
+		match text.to_uppercase().replace('-', "_").as_str() {
+			"ERROR"      => Ok(Ragged::ERROR),
+			"PAD"        => Ok(Ragged::PAD),
+			"TRUNCATE"   => Ok(Ragged::TRUNCATE),
+			"MERGE_TAIL" => Ok(Ragged::MERGE_TAIL),
+			_ => Err(format!("Invalid ragged rows handling: {}", text)),
+		}
+	}
+}
+
+impl fmt::Display for Ragged {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Ragged::ERROR      => write!(f, "ERROR"     ),
+			Ragged::PAD        => write!(f, "PAD"       ),
+			Ragged::TRUNCATE   => write!(f, "TRUNCATE"  ),
+			Ragged::MERGE_TAIL => write!(f, "MERGE-TAIL"),
+		}
+	}
+}
+
+/// When plain text tables are styled with terminal colors.
+#[derive(Copy, Clone, Default, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+pub enum Color {
+	/// Style the output when it goes to a terminal and `NO_COLOR` is not set.
+	#[default]
+	AUTO,
+
+	/// Always style the output.
+	ALWAYS,
+
+	/// Never style the output.
+	NEVER,
+}
+
+impl FromStr for Color {
+	type Err = String;
+
+	fn from_str(text: &str) -> Result<Color, Self::Err> {
+		match text.to_uppercase().as_str() {
+			"AUTO"   => Ok(Color::AUTO),
+			"ALWAYS" => Ok(Color::ALWAYS),
+			"NEVER"  => Ok(Color::NEVER),
+			_ => Err(format!("Invalid color mode: {}", text)),
+		}
+	}
+}
+
+impl fmt::Display for Color {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Color::AUTO   => write!(f, "AUTO"  ),
+			Color::ALWAYS => write!(f, "ALWAYS"),
+			Color::NEVER  => write!(f, "NEVER" ),
+		}
+	}
+}
 
 /// Builder for configuring and formatting text into columns.
 ///
@@ -18,6 +259,14 @@ pub struct TableBuilder {
 	#[arg(default_value = " ", long, short)]
 	pub ifs: String,
 
+	/// Layout of the input, structured tables detect their own header rows
+	#[arg(value_enum, default_value_t = InputFormat::DELIMITED, long)]
+	pub input_format: InputFormat,
+
+	/// Column widths for fixed-width input, detected from whitespace gutters if not given
+	#[arg(long = "widths", value_delimiter = ',')]
+	pub field_widths: Vec<usize>,
+
 	/// Output Field Separator
 	#[arg(default_value = " ", long, short)]
 	pub ofs: String,
@@ -55,6 +304,14 @@ pub struct TableBuilder {
 	#[arg(long, short = 'E')]
 	pub no_ellipsis: bool,
 
+	/// Marker for truncated text, `{}` stands for the number of hidden characters, e.g. "[+{}]"
+	#[arg(default_value = "...", long)]
+	pub ellipsis: String,
+
+	/// Maximum number of lines of a wrapped cell, the last line kept ends with the ellipsis
+	#[arg(long)]
+	pub max_lines: Option<usize>,
+
 	/// Use decimal precision for numbers
 	#[arg(short, long)]
 	pub pad_decimal_digits: bool,
@@ -76,11 +333,158 @@ pub struct TableBuilder {
 	#[arg(short, long)]
 	pub thousand_separator: char,
 
+	/// How integer digits are grouped by the thousand separator
+	#[arg(value_enum, default_value_t = Grouping::THOUSANDS, long)]
+	pub grouping: Grouping,
+
+	/// Number locale, overrides the decimal separator, thousand separator and grouping
+	#[arg(value_enum, long, alias = "output-locale")]
+	pub locale: Option<Locale>,
+
+	/// Decimal separator of numbers in the input, defaults to the decimal separator
+	#[arg(long)]
+	pub input_decimal_separator: Option<char>,
+
+	/// Thousand separator of numbers in the input, defaults to the thousand separator
+	#[arg(long)]
+	pub input_thousand_separator: Option<char>,
+
+	/// Number locale of the input, overrides the input separators
+	#[arg(value_enum, long)]
+	pub input_locale: Option<Locale>,
+
+	/// How numbers are displayed
+	#[arg(value_enum, default_value_t = NumberDisplay::PLAIN, long)]
+	pub number_display: NumberDisplay,
+
+	/// How numbers of one column are displayed, as <column>:<display>, e.g. size:bytes or 3:si
+	#[arg(long = "column-display", value_delimiter = ',')]
+	pub column_displays: Vec<ColumnDisplay>,
+
+	/// Currency symbol added to numbers
+	#[arg(long)]
+	pub currency: Option<String>,
+
+	/// Whether the currency symbol goes before or after the number
+	#[arg(value_enum, default_value_t = CurrencyPosition::PREFIX, long)]
+	pub currency_position: CurrencyPosition,
+
+	/// Show negative numbers in parentheses, as in accounting
+	#[arg(long)]
+	pub accounting: bool,
+
+	/// Show a `+` sign on positive numbers
+	#[arg(long)]
+	pub plus_sign: bool,
+
+	/// How numbers are rounded to the maximum decimal digits
+	#[arg(value_enum, default_value_t = Rounding::HALF_UP, long)]
+	pub rounding: Rounding,
+
+	/// Only read plain decimals as numbers, not `inf`, `NaN` or exponents such as `1e5`
+	#[arg(long)]
+	pub strict: bool,
+
+	/// Round numbers to this many significant figures instead of the maximum decimal digits
+	#[arg(long, value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+	pub sig_figs: Option<usize>,
+
+	/// Pad every numeric column to the fewest decimal digits that fit all of its numbers
+	#[arg(long)]
+	pub auto_precision: bool,
+
+	/// Share of a column's non-null cells, from 0 to 1, that must agree on the column type
+	#[arg(default_value_t = 0.5, long)]
+	pub type_threshold: f64,
+
+	/// Sort the data rows by this column, by index or header text, according to its type
+	#[arg(long)]
+	pub sort: Option<ColumnRef>,
+
+	/// Sort in descending order
+	#[arg(long)]
+	pub sort_descending: bool,
+
+	/// `strftime` pattern for the timestamps of date and date-time columns, e.g. "%d %b %H:%M"
+	#[arg(long)]
+	pub date_format: Option<String>,
+
+	/// Show timestamps relative to the reference time, e.g. "3m ago"
+	#[arg(long)]
+	pub relative_time: bool,
+
+	/// Reference time for relative timestamps, e.g. 2024-03-01T12:00:00Z, defaults to now
+	#[arg(long)]
+	pub reference_time: Option<DateTime<FixedOffset>>,
+
+	/// Convert timestamps to this UTC offset, e.g. +02:00
+	#[arg(long, allow_hyphen_values = true)]
+	pub timezone: Option<FixedOffset>,
+
+	/// Cell values that stand for a missing value, defaults to "", -, NULL, null, N/A, n/a and NA
+	#[arg(long, value_delimiter = ',')]
+	pub null_values: Option<Vec<String>>,
+
+	/// Text shown in place of missing values, including the cells missing from short rows
+	#[arg(long)]
+	pub null_display: Option<String>,
+
+	/// Columns holding Unix timestamps in seconds or milliseconds, by index or header text
+	#[arg(long, value_delimiter = ',')]
+	pub epoch_columns: Vec<ColumnRef>,
+
 	#[arg(default_value_t = Alignment::AUTO)]
 	#[arg(value_enum)]
 	#[arg(short, long)]
 	pub alignment: Alignment,
 
+	/// Number of trailing rows treated as footer rows
+	#[arg(default_value_t = 0, long)]
+	pub footer_count: usize,
+
+	/// How rows with more or fewer fields than the header row are handled
+	#[arg(value_enum, default_value_t = Ragged::PAD, long)]
+	pub ragged: Ragged,
+
+	/// Output format
+	#[arg(value_enum, default_value_t = OutputFormat::PLAIN, long)]
+	pub output: OutputFormat,
+
+	/// Wrap HTML output in a complete HTML document
+	#[arg(long)]
+	pub standalone: bool,
+
+	/// Add inline `text-align` styles to HTML cells
+	#[arg(long)]
+	pub inline_styles: bool,
+
+	/// Use booktabs rules in LaTeX output
+	#[arg(long)]
+	pub booktabs: bool,
+
+	/// Use siunitx `S` columns for numbers in LaTeX output
+	#[arg(long)]
+	pub siunitx: bool,
+
+	/// Style plain output with bold headers, dimmed dividers, striped rows and red negatives
+	#[arg(value_enum, default_value_t = Color::AUTO, long)]
+	pub color: Color,
+
+	/// Style matching cells or rows in plain output, e.g. `latency>500:red` or `status=FAIL:row,bold`
+	#[arg(long = "highlight")]
+	pub highlights: Vec<Highlight>,
+
+	/// Rows parsed from the input according to `input_format`.
+	///
+	/// Every later step reads the input through these rows, header rows and the column
+	/// width limits row included.
+	#[clap(skip)]
+	pub rows: Option<Vec<Vec<String>>>,
+
+	/// The 1-based input line every row of `rows` starts on.
+	#[clap(skip)]
+	pub row_lines: Option<Vec<usize>>,
+
 	/// These field are computed and cached
 	/// An instance of `Table` from the `prettytable` crate.
 	/// to collect and apply final formatting
@@ -135,6 +539,21 @@ pub struct TableBuilder {
 	#[clap(skip)]
 	pub numeric_columns: Option<Vec<bool>>,
 
+	/// Cached type of every column, inferred from the data rows.
+	#[clap(skip)]
+	pub column_types: Option<Vec<ColumnType>>,
+
+	/// Cached status indicating whether each data cell is a formatted number.
+	///
+	/// Cells are laid out from this instead of being read again, so formatted numbers such
+	/// as `1.23e4` or `+5` are not parsed a second time.
+	#[clap(skip)]
+	pub numeric_cells: Option<Vec<Vec<bool>>>,
+
+	/// Highlight styles of the data rows: the style of the whole row, and of each cell.
+	#[clap(skip)]
+	pub highlight_styles: Option<Vec<(String, Vec<String>)>>,
+
 	/// number of columns after parsing data
 	#[clap(skip)]
 	pub column_count:	Option<usize>,
@@ -147,6 +566,8 @@ impl Default for TableBuilder  {
 			input:                    None, // No input
 			ifs:           " ".to_string(), // Default input field separator
 			ofs:           " ".to_string(), // Default output field separator
+			input_format: InputFormat::DELIMITED, // Default split lines on ifs
+			field_widths:           vec![], // Default detect fixed-width columns
 			header_index:                1, // Default header at row 1
 			header_count:                1, // Default 1 header row
 			column_width_limits_index:   0, // Default no column width row
@@ -155,12 +576,50 @@ impl Default for TableBuilder  {
 			max_cell_width:             80, // Default maximum cell width
 			frame:         Frame::TRUNCATE, // Default truncate text
 			no_ellipsis:             false, // Default no ellipsis on truncate
+			ellipsis:    "...".to_string(), // Default three dots
+			max_lines:                None, // Default keep every wrapped line
 			pad_decimal_digits:      false, // Default dont pad decimal digits
 			max_decimal_digits:          2, // Default maximum decimal digits
 			decimal_separator:         '.', // Default decimal separator
 			use_thousand_separator:  false, // Default don't add thousand separator
 			thousand_separator:        ',', // Default thousand seperator char ,
+			grouping:  Grouping::THOUSANDS, // Default groups of three
+			locale:                   None, // Default use the separators as given
+			input_decimal_separator:  None, // Default read with the decimal separator
+			input_thousand_separator: None, // Default read with the thousand separator
+			input_locale:             None, // Default read with the output locale
+			number_display: NumberDisplay::PLAIN, // Default plain decimals
+			column_displays:        vec![], // Default same display for every column
+			currency:                 None, // Default no currency symbol
+			currency_position: CurrencyPosition::PREFIX, // Default symbol before the number
+			accounting:              false, // Default `-` for negative numbers
+			plus_sign:               false, // Default no sign on positive numbers
+			rounding:    Rounding::HALF_UP, // Default round halves away from zero
+			strict:                  false, // Default also read exponents, inf and NaN
+			sig_figs:                 None, // Default round to max_decimal_digits
+			auto_precision:          false, // Default decimal digits follow each number
+			type_threshold:            0.5, // Default the majority type wins
+			sort:                     None, // Default keep the input order
+			sort_descending:         false, // Default ascending order
+			date_format:              None, // Default keep timestamps as they are
+			relative_time:           false, // Default absolute timestamps
+			reference_time:           None, // Default relative to now
+			timezone:                 None, // Default keep the offset of each timestamp
+			epoch_columns:          vec![], // Default no Unix timestamp columns
+			null_values:              None, // Default the common null values
+			null_display:             None, // Default show missing values as they are
 			alignment:     Alignment::AUTO, // Default align numeric columns to the right
+			footer_count:                0, // Default no footer rows
+			ragged:            Ragged::PAD, // Default keep every field
+			output:    OutputFormat::PLAIN, // Default plain text columns
+			standalone:              false, // Default bare HTML table
+			inline_styles:           false, // Default no inline HTML styles
+			booktabs:                false, // Default \hline rules in LaTeX
+			siunitx:                 false, // Default `r` columns for numbers in LaTeX
+			color:             Color::AUTO, // Default style terminal output
+			highlights:             vec![], // Default no highlight rules
+			rows:                     None, // Unknown parsed rows
+			row_lines:                None, // Unknown row line numbers
 			table:                    None, // Unknown prettytable
 			column_width_limits:      None, // Unknown column width limits
 			header_column_widths:     None, // Unknown header column widths
@@ -169,6 +628,9 @@ impl Default for TableBuilder  {
 			headers:                  None, // Unknown header rows
 			data:                     None, // Unknown data rows
 			numeric_columns:          None, // Unknown numeric columns
+			column_types:             None, // Unknown column types
+			numeric_cells:            None, // Unknown numeric cells
+			highlight_styles:         None, // Unknown highlight styles
 			column_count:             None, // Unknown column count
 		}
 	}
@@ -194,8 +656,13 @@ impl TableBuilder {
 			}
 		};
 
-		// Clean and set the input field
-		builder.input = Some(clean(Some(&input_data)));
+		// Drop trailing whitespace, leading whitespace is kept for fixed-width input and blank
+		// lines are kept so rows can be reported by line number
+		builder.input = Some(input_data
+			.lines()
+			.map(str::trim_end)
+			.collect::<Vec<_>>()
+			.join("\n"));
 		builder // Return the modified builder
 	}
 
@@ -209,6 +676,22 @@ impl TableBuilder {
 		self
 	}
 
+	pub fn set_input_format(&mut self, input_format: InputFormat) -> &mut Self {
+		self.input_format = input_format;
+		self
+	}
+
+	/// Sets the column widths used to split fixed-width input.
+	///
+	/// # Arguments
+	///
+	/// * `field_widths` - The display width of each column, the last column takes the rest of
+	///   the line. When empty, the columns are detected from the whitespace gutters.
+	pub fn set_field_widths(&mut self, field_widths: Vec<usize>) -> &mut Self {
+		self.field_widths = field_widths;
+		self
+	}
+
 	/// Sets the index of the header row in the input data.
 	///
 	/// # Arguments
@@ -271,6 +754,16 @@ impl TableBuilder {
 		self
 	}
 
+	pub fn set_ellipsis(&mut self, ellipsis: String) -> &mut Self {
+		self.ellipsis = ellipsis;
+		self
+	}
+
+	pub fn set_max_lines(&mut self, max_lines: Option<usize>) -> &mut Self {
+		self.max_lines = max_lines;
+		self
+	}
+
 	pub fn set_pad_decimal_digits(&mut self, pad_decimal_digits: bool) -> &mut Self {
 		self.pad_decimal_digits = pad_decimal_digits;
 		self
@@ -301,16 +794,309 @@ impl TableBuilder {
 		self
 	}
 
+	pub fn set_grouping(&mut self, grouping: Grouping) -> &mut Self {
+		self.grouping = grouping;
+		self
+	}
+
+	/// Applies a number locale preset to both reading and formatting numbers.
+	///
+	/// # Arguments
+	///
+	/// * `locale` - The locale preset, replacing the decimal separator, the thousand
+	///   separator and the grouping.
+	pub fn set_locale(&mut self, locale: Locale) -> &mut Self {
+		self.locale = Some(locale);
+		self.decimal_separator = locale.decimal_separator();
+		self.thousand_separator = locale.thousand_separator();
+		self.grouping = locale.grouping();
+		self
+	}
+
+	pub fn set_number_display(&mut self, number_display: NumberDisplay) -> &mut Self {
+		self.number_display = number_display;
+		self
+	}
+
+	/// Sets the number display mode of individual columns.
+	///
+	/// # Arguments
+	///
+	/// * `column_displays` - Display modes overriding `number_display` for their column.
+	pub fn set_column_displays(&mut self, column_displays: Vec<ColumnDisplay>) -> &mut Self {
+		self.column_displays = column_displays;
+		self
+	}
+
+	pub fn set_currency(&mut self, currency: Option<String>) -> &mut Self {
+		self.currency = currency;
+		self
+	}
+
+	pub fn set_currency_position(&mut self, currency_position: CurrencyPosition) -> &mut Self {
+		self.currency_position = currency_position;
+		self
+	}
+
+	pub fn set_accounting(&mut self, accounting: bool) -> &mut Self {
+		self.accounting = accounting;
+		self
+	}
+
+	pub fn set_plus_sign(&mut self, plus_sign: bool) -> &mut Self {
+		self.plus_sign = plus_sign;
+		self
+	}
+
+	pub fn set_rounding(&mut self, rounding: Rounding) -> &mut Self {
+		self.rounding = rounding;
+		self
+	}
+
+	pub fn set_strict(&mut self, strict: bool) -> &mut Self {
+		self.strict = strict;
+		self
+	}
+
+	pub fn set_sig_figs(&mut self, sig_figs: Option<usize>) -> &mut Self {
+		self.sig_figs = sig_figs;
+		self
+	}
+
+	pub fn set_auto_precision(&mut self, auto_precision: bool) -> &mut Self {
+		self.auto_precision = auto_precision;
+		self
+	}
+
+	pub fn set_date_format(&mut self, date_format: Option<String>) -> &mut Self {
+		self.date_format = date_format;
+		self
+	}
+
+	/// Shows timestamps relative to a reference time.
+	///
+	/// # Arguments
+	///
+	/// * `relative_time` - Whether to show timestamps as `3m ago` or `in 2h`.
+	/// * `reference_time` - The time they are relative to, or `None` for now.
+	pub fn set_relative_time(&mut self, relative_time: bool, reference_time: Option<DateTime<FixedOffset>>) -> &mut Self {
+		self.relative_time = relative_time;
+		self.reference_time = reference_time;
+		self
+	}
+
+	pub fn set_timezone(&mut self, timezone: Option<FixedOffset>) -> &mut Self {
+		self.timezone = timezone;
+		self
+	}
+
+	/// Sets the cell values that stand for a missing value.
+	///
+	/// # Arguments
+	///
+	/// * `null_values` - The null values, or `None` for `infer::NULL_VALUES`.
+	pub fn set_null_values(&mut self, null_values: Option<Vec<String>>) -> &mut Self {
+		self.null_values = null_values;
+		self
+	}
+
+	pub fn set_null_display(&mut self, null_display: Option<String>) -> &mut Self {
+		self.null_display = null_display;
+		self
+	}
+
+	pub fn set_epoch_columns(&mut self, epoch_columns: Vec<ColumnRef>) -> &mut Self {
+		self.epoch_columns = epoch_columns;
+		self
+	}
+
+	pub fn set_type_threshold(&mut self, type_threshold: f64) -> &mut Self {
+		self.type_threshold = type_threshold;
+		self
+	}
+
+	/// Sets the column the data rows are sorted by.
+	///
+	/// # Arguments
+	///
+	/// * `sort` - The column, or `None` to keep the input order.
+	/// * `descending` - Whether to sort in descending order.
+	pub fn set_sort(&mut self, sort: Option<ColumnRef>, descending: bool) -> &mut Self {
+		self.sort = sort;
+		self.sort_descending = descending;
+		self
+	}
+
+	pub fn set_input_decimal_separator(&mut self, input_decimal_separator: Option<char>) -> &mut Self {
+		self.input_decimal_separator = input_decimal_separator;
+		self
+	}
+
+	pub fn set_input_thousand_separator(&mut self, input_thousand_separator: Option<char>) -> &mut Self {
+		self.input_thousand_separator = input_thousand_separator;
+		self
+	}
+
+	/// Applies a number locale preset to reading numbers only.
+	///
+	/// Cells are read with the separators of `locale` and formatted with the output
+	/// separators, so a German CSV can be displayed with English numbers.
+	///
+	/// # Arguments
+	///
+	/// * `locale` - The locale preset of the input.
+	pub fn set_input_locale(&mut self, locale: Locale) -> &mut Self {
+		self.input_locale = Some(locale);
+		self.input_decimal_separator = Some(locale.decimal_separator());
+		self.input_thousand_separator = Some(locale.thousand_separator());
+		self
+	}
+
+	/// Sets the number of rows at the end of the data to treat as footer rows.
+	///
+	/// # Arguments
+	///
+	/// * `count` - The number of trailing data rows rendered as footers.
+	pub fn set_footer_count(&mut self, count: usize) -> &mut Self {
+		self.footer_count = count;
+		self
+	}
+
+	/// Sets how rows with more or fewer fields than the header row are handled.
+	pub fn set_ragged(&mut self, ragged: Ragged) -> &mut Self {
+		self.ragged = ragged;
+		self
+	}
+
+	pub fn set_output(&mut self, output: OutputFormat) -> &mut Self {
+		self.output = output;
+		self
+	}
+
+	pub fn set_standalone(&mut self, standalone: bool) -> &mut Self {
+		self.standalone = standalone;
+		self
+	}
+
+	pub fn set_inline_styles(&mut self, inline_styles: bool) -> &mut Self {
+		self.inline_styles = inline_styles;
+		self
+	}
+
+	pub fn set_booktabs(&mut self, booktabs: bool) -> &mut Self {
+		self.booktabs = booktabs;
+		self
+	}
+
+	pub fn set_siunitx(&mut self, siunitx: bool) -> &mut Self {
+		self.siunitx = siunitx;
+		self
+	}
+
+	pub fn set_color(&mut self, color: Color) -> &mut Self {
+		self.color = color;
+		self
+	}
+
+	pub fn set_highlights(&mut self, highlights: Vec<Highlight>) -> &mut Self {
+		self.highlights = highlights;
+		self
+	}
+
 }
 
 impl TableBuilder {
 
+	/// Parses the input into rows of cells, according to `input_format`.
+	///
+	/// If the rows have already been parsed, this method returns the cached value.
+	/// `InputFormat::DELIMITED` splits each line by the input field separator (`ifs`), and
+	/// `InputFormat::FIXED` slices each line at `field_widths` or at detected column gutters. The
+	/// structured formats (Markdown, Org and box tables) strip borders and separator rows and
+	/// detect the header rows themselves, which replaces `header_index` and `header_count`.
+	///
+	/// # Returns
+	///
+	/// * A reference to the parsed rows.
+	///
+	/// # Side Effects
+	///
+	/// * Caches the rows in `self.rows`, and sets `self.header_index` and `self.header_count`
+	///   when the input format declares its header rows.
+	pub fn rows(&mut self) -> &Vec<Vec<String>> {
+		if let Some(ref rows) = self.rows {
+			return rows;
+		}
+
+		let input = self.input.clone().unwrap_or_default();
+		let table = parse::parse(&input, self.input_format, &self.ifs, &self.field_widths);
+
+		// Use the header rows detected in structured input
+		if let Some(header_count) = table.header_count {
+			self.header_index = if header_count > 0 { 1 } else { 0 };
+			self.header_count = header_count;
+		}
+
+		// Bring ragged rows to the width of the header row
+		let field_count = Self::field_count(&table.rows, self.header_index);
+		let mut rows = table.rows;
+		for row in rows.iter_mut().filter(|row| row.len() > field_count && field_count > 0) {
+			match self.ragged {
+				Ragged::ERROR | Ragged::PAD => {},
+				Ragged::TRUNCATE => row.truncate(field_count),
+				Ragged::MERGE_TAIL => {
+					let tail = row.split_off(field_count - 1);
+					row.push(tail.join(&self.ifs));
+				},
+			}
+		}
+
+		self.rows = Some(rows);
+		self.row_lines = Some(table.lines);
+
+		self.rows.as_ref().unwrap()
+	}
+
+	/// Returns the number of fields rows are expected to have: the length of the first header
+	/// row, or of the first row without headers.
+	fn field_count(rows: &[Vec<String>], header_index: usize) -> usize {
+		rows.get(header_index.saturating_sub(1)).map(Vec::len).unwrap_or(0)
+	}
+
+	/// Checks the rows against `ragged`.
+	///
+	/// # Errors
+	///
+	/// With `Ragged::ERROR`, returns an error listing the line number and field count of every
+	/// row with more or fewer fields than the header row.
+	pub fn validate(&mut self) -> Result<()> {
+		if self.ragged != Ragged::ERROR {
+			return Ok(());
+		}
+
+		let rows = self.rows().clone();
+		let lines = self.row_lines.clone().unwrap_or_default();
+		let field_count = Self::field_count(&rows, self.header_index);
+
+		let ragged: Vec<String> = rows.iter()
+			.zip(lines)
+			.filter(|(row, _)| row.len() != field_count)
+			.map(|(row, line)| format!("line {}: {} fields, expected {}", line, row.len(), field_count))
+			.collect();
+
+		if ragged.is_empty() {
+			Ok(())
+		} else {
+			Err(eyre!("Ragged rows:\n  {}", ragged.join("\n  ")))
+		}
+	}
+
 	/// Determines and returns the number of columns in the input data.
 	///
 	/// If the column count has already been calculated, this method returns the cached value.
-	/// Otherwise, it calculates the number of columns by iterating over all lines in the input,
-	/// splitting each line by the input field separator (`ifs`), and finding the maximum number of fields (columns)
-	/// across all rows. This ensures that the widest row determines the total column count.
+	/// Otherwise, it calculates the number of columns by finding the maximum number of fields
+	/// (columns) across all parsed `rows()`. This ensures that the widest row determines the
+	/// total column count.
 	///
 	/// If the input contains no lines, the column count defaults to `0`.
 	///
@@ -326,7 +1112,7 @@ impl TableBuilder {
 	///
 	/// # Example
 	///
-	/// ```
+	/// ```ignore
 	/// let column_count = your_instance.column_count();
 	/// println!("The table has {} columns.", column_count);
 	/// ```
@@ -336,8 +1122,8 @@ impl TableBuilder {
 		}
 
 		self.column_count = Some(
-			self.input.clone().expect("REASON").lines()
-				.map(|line| line.split(&self.ifs).count())
+			self.rows().iter()
+				.map(Vec::len)
 				.max()
 				.unwrap_or(0)
 		);
@@ -366,10 +1152,10 @@ impl TableBuilder {
 		}
 
 		self.column_width_limits = Some(if self.column_width_limits_index > 0 {
-			self.input.clone().expect("REASON").lines()
-				.nth(self.column_width_limits_index)
-				.map(|line| {
-					line.split(&self.ifs)
+			self.rows().clone()
+				.get(self.column_width_limits_index - 1)
+				.map(|row| {
+					row.iter()
 						.map(|s| s.trim().parse::<usize>().unwrap_or(self.max_cell_width))
 						.map(|width| if width == 0 { self.max_cell_width } else { width })
 						.collect::<Vec<usize>>()
@@ -423,16 +1209,8 @@ impl TableBuilder {
 
 	/// Returns a reference to the `numeric_columns` vector, initializing it if necessary.
 	///
-	/// This vector tracks whether each column contains only numeric values. Initially, all columns are
-	/// assumed to be numeric (set to `true`). During data processing, if any value in a column is found to
-	/// be non-numeric, the entire column is marked as non-numeric (`false`).
-	///
-	/// If the `numeric_columns` vector has already been calculated, this method simply returns a reference
-	/// to the existing data. If not, it initializes the vector with a length equal to `self.column_count()`,
-	/// setting each value to `true` (assuming all columns are numeric until proven otherwise).
-	///
-	/// This method also ensures that `self.column_count()` is calculated as part of the initialization process.
-	/// The actual determination of numeric status for each column happens during the execution of the `self.data()` method.
+	/// A column is numeric when its inferred type holds quantities: integers, decimals,
+	/// percentages or durations. Numeric columns are aligned to the right.
 	///
 	/// # Returns
 	/// 
@@ -440,7 +1218,7 @@ impl TableBuilder {
 	///
 	/// # Example
 	///
-	/// ```
+	/// ```ignore
 	/// let numeric_columns = your_instance.numeric_columns();
 	/// println!("Are columns numeric? {:?}", numeric_columns);
 	/// ```
@@ -450,12 +1228,204 @@ impl TableBuilder {
 			return numeric_columns;
 		}
 
-		self.numeric_columns = Some(vec![true; self.column_count()]);
+		self.numeric_columns = Some(self.column_types().iter().map(ColumnType::is_numeric).collect());
 
 		self.numeric_columns.as_ref().unwrap()
 
 	}
 
+	/// Returns the type of every column, inferring it if necessary.
+	///
+	/// Each column type is inferred from the data rows as read from the input, skipping null
+	/// cells: the most common cell type wins when it covers at least `type_threshold` of the
+	/// other cells, otherwise the column is text.
+	///
+	/// # Returns
+	///
+	/// A reference to the `ColumnType` of every column.
+	pub fn column_types(&mut self) -> &Vec<ColumnType> {
+		if let Some(ref column_types) = self.column_types {
+			return column_types;
+		}
+
+		let rows = self.data_rows();
+		let reader = self.cell_formatter(String::new(), NumberDisplay::PLAIN);
+		let number = |text: &str| reader.parse_number(text);
+		let null_values = self.null_values();
+		let nulls: Vec<&str> = null_values.iter().map(String::as_str).collect();
+
+		let mut column_types: Vec<ColumnType> = (0..self.column_count())
+			.map(|i| ColumnType::infer(
+				rows.iter().map(|row| row.get(i).map(String::as_str).unwrap_or("")),
+				&nulls,
+				self.type_threshold,
+				&number,
+			))
+			.collect();
+
+		// Unix timestamps look like integers, they are only read as such when asked to
+		for i in self.epoch_column_indexes() {
+			column_types[i] = ColumnType::DATETIME;
+		}
+
+		self.column_types = Some(column_types);
+
+		self.column_types.as_ref().unwrap()
+	}
+
+	/// Finds the 0-based indexes of `epoch_columns`, warning about unknown columns.
+	fn epoch_column_indexes(&mut self) -> Vec<usize> {
+		self.epoch_columns.clone().iter()
+			.filter_map(|column| {
+				let index = self.column_index(column);
+				if index.is_none() {
+					eprintln!("Warning: No such column for --epoch-columns: {}", column);
+				}
+				index
+			})
+			.collect()
+	}
+
+	/// Reformats a timestamp cell with the date options.
+	///
+	/// The timestamp is converted to `timezone`, then shown relative to the reference time or
+	/// with `date_format`. Without a pattern, dates keep their `%Y-%m-%d` layout and date-times
+	/// are shown in RFC 3339, so Unix timestamps and converted ones are readable.
+	///
+	/// # Returns
+	///
+	/// The reformatted cell, or `None` to keep the cell as it is.
+	fn format_timestamp(&self, cell: &str, column_type: ColumnType, is_epoch: bool) -> Option<String> {
+		if self.date_format.is_none() && !self.relative_time && self.timezone.is_none() && !is_epoch {
+			return None;
+		}
+
+		let timestamp = datetime::parse(cell, is_epoch)?;
+		let timestamp = match self.timezone {
+			Some(timezone) => timestamp.with_timezone(&timezone),
+			None => timestamp,
+		};
+
+		if self.relative_time {
+			let reference = self.reference_time.unwrap_or_else(datetime::now);
+			return Some(datetime::relative(&timestamp, &reference));
+		}
+
+		let pattern = match (&self.date_format, column_type) {
+			(Some(pattern), _) => pattern.as_str(),
+			(None, ColumnType::DATE) => "%Y-%m-%d",
+			(None, _) => "%Y-%m-%dT%H:%M:%S%:z",
+		};
+
+		datetime::format(&timestamp, pattern)
+	}
+
+	/// Returns the cell values that stand for a missing value.
+	pub fn null_values(&self) -> Vec<String> {
+		match self.null_values {
+			Some(ref null_values) => null_values.iter().map(|value| value.trim().to_string()).collect(),
+			None => infer::NULL_VALUES.iter().map(|value| value.to_string()).collect(),
+		}
+	}
+
+	/// Returns the data rows as read from the input, without the header rows and the column
+	/// width limits row, sorted when a sort column is set.
+	///
+	/// Rows shorter than `column_count()` are padded with empty cells, which are null values
+	/// unless `null_values` leaves out the empty string. Footer rows stay at the end, they are
+	/// not sorted with the other rows.
+	fn data_rows(&mut self) -> Vec<Vec<String>> {
+		// Parse the input first, structured input sets the header rows
+		let rows = self.rows().clone();
+
+		// Adjust for 1-indexed header and column width limits index
+		let header_start = self.header_index.saturating_sub(1);
+		let header_end   = if self.header_index > 0 { header_start + self.header_count } else { 0 };
+		let column_width_limits_index = self.column_width_limits_index.checked_sub(1);
+
+		let mut data_rows: Vec<Vec<String>> = rows.into_iter()
+			.enumerate()
+			.filter(|(i, _)| !((header_start <= *i && *i < header_end) || Some(*i) == column_width_limits_index))
+			.map(|(_, row)| row)
+			.collect();
+
+		// Give short rows the missing cells
+		let column_count = self.column_count();
+		for row in data_rows.iter_mut() {
+			row.resize(column_count.max(row.len()), String::new());
+		}
+
+		let null_values = self.null_values();
+		let nulls: Vec<&str> = null_values.iter().map(String::as_str).collect();
+
+		let Some(sort) = self.sort.clone() else {
+			return data_rows;
+		};
+
+		let Some(column) = self.column_index(&sort) else {
+			eprintln!("Warning: No such column for --sort: {}", sort);
+			return data_rows;
+		};
+
+		// Infer the column type from the rows in input order, then sort all but the footer rows
+		let column_type = match self.column_types {
+			Some(ref column_types) => column_types[column],
+			None => {
+				let reader = self.cell_formatter(String::new(), NumberDisplay::PLAIN);
+				ColumnType::infer(
+					data_rows.iter().map(|row| row.get(column).map(String::as_str).unwrap_or("")),
+					&nulls,
+					self.type_threshold,
+					&|text: &str| reader.parse_number(text),
+				)
+			},
+		};
+
+		let reader = self.cell_formatter(String::new(), NumberDisplay::PLAIN);
+		let number = |text: &str| reader.parse_number(text);
+		let footer_start = data_rows.len().saturating_sub(self.footer_count);
+		let cell = |row: &Vec<String>| row.get(column).cloned().unwrap_or_default();
+
+		data_rows[..footer_start].sort_by(|a, b| {
+			column_type.compare(&cell(a), &cell(b), &nulls, self.sort_descending, &number)
+		});
+
+		data_rows
+	}
+
+	/// Finds the 0-based index of a column selected by index or by header text.
+	///
+	/// Names are matched against the trimmed cells of the first header row.
+	///
+	/// # Returns
+	///
+	/// * The column index, or `None` if no such column exists.
+	pub fn column_index(&mut self, column: &ColumnRef) -> Option<usize> {
+		match column {
+			ColumnRef::Index(index) => Some(index - 1).filter(|&i| i < self.column_count()),
+			ColumnRef::Name(name) => self.headers()
+				.first()
+				.and_then(|header| header.iter().position(|cell| cell.trim() == name)),
+		}
+	}
+
+	/// Returns the number display mode of every column.
+	///
+	/// Columns default to `number_display`, `column_displays` override single columns. The
+	/// display mode only changes how numbers are shown: `rows()` keeps the raw values.
+	pub fn number_displays(&mut self) -> Vec<NumberDisplay> {
+		let mut number_displays = vec![self.number_display; self.column_count()];
+
+		for column_display in self.column_displays.clone() {
+			match self.column_index(&column_display.column) {
+				Some(i) => number_displays[i] = column_display.display,
+				None => eprintln!("Warning: No such column for --column-display: {}", column_display.column),
+			}
+		}
+
+		number_displays
+	}
+
 	/// Clears the cached headers to allow for recalculation during the next headers retrieval.
 	///
 	/// This method sets the `headers` field to `None`, indicating that the headers need to be recalculated 
@@ -464,11 +1434,11 @@ impl TableBuilder {
 	///
 	/// # Example
 	///
-	/// ```
+	/// ```ignore
 	/// let exa = TextFormatter::new()
 	///  ...
-	///	 .clear_headers()
-	///	 .headers(); // Forces a recalculation of headers
+	///     .clear_headers()
+	///     .headers(); // Forces a recalculation of headers
 	/// ```
 	///
 	/// # Returns
@@ -498,7 +1468,7 @@ impl TableBuilder {
 	///
 	/// # Example
 	///
-	/// ```
+	/// ```ignore
 	/// let headers = formatter.headers();
 	/// ```
 	pub fn headers(&mut self) -> &Vec<Vec<String>> {
@@ -510,21 +1480,23 @@ impl TableBuilder {
 		let mut column_widths = self.header_column_widths().clone();
 		let column_width_limits = self.column_width_limits().clone();
 
+		// Parse the input first, structured input sets the header rows
+		self.rows();
+
 		// Initialize headers if header_index and header_count are set
 		self.headers = Some(if self.header_index > 0 && self.header_count > 0 {
 			// Initialize the column widths vector
 
-			// Iterate over the specified header lines
-			self.input.clone().expect("REASON").lines()
+			// Iterate over the specified header rows
+			self.rows().clone().iter()
 				.skip(self.header_index - 1)
 				.take(self.header_count)
-				.map(|line| {
-					// Split each line by the input field separator (IFS)
-					line.split(&self.ifs)
+				.map(|row| {
+					row.iter()
 						.enumerate()
 						.map(|(i, s)| {
 							let header_cell = s.to_string();
-							let width = header_cell.len(); // Get length of the header cell
+							let width = ansi::width(&header_cell); // Get the visible width of the header cell
 
 							// Update the column width for the current column (if within bounds)
 							if i < column_widths.len() {
@@ -566,7 +1538,7 @@ impl TableBuilder {
 	///
 	/// # Example
 	///
-	/// ```
+	/// ```ignore
 	/// let processed_data = formatter.data();
 	/// ```
 	pub fn data(&mut self) -> &Vec<Vec<String>> {
@@ -575,71 +1547,198 @@ impl TableBuilder {
 			return data;
 		}
 
-		// Adjust for 1-indexed header and column width limits index
-		let header_start = self.header_index.saturating_sub(1);
-		let header_end   = header_start + self.header_count;
-		let column_width_limits_index = self.column_width_limits_index.saturating_sub(1);
+		// Rows that are not headers and not the column_width_limits_index row
+		let data_rows = self.data_rows();
 
 		let column_width_limits = self.column_width_limits().clone();
 		let mut column_widths   = self.data_column_widths().clone();
-		let mut numeric_columns = self.numeric_columns().clone();
+		let column_types        = self.column_types().clone();
+		let epoch_columns: Vec<usize> = self.epoch_columns.clone().iter().filter_map(|column| self.column_index(column)).collect();
+		let number_displays     = self.number_displays();
+		let null_values         = self.null_values();
+		let mut numeric_cells: Vec<Vec<bool>> = Vec::new();
+
+		if let Some(ref pattern) = self.date_format {
+			if datetime::format(&datetime::now(), pattern).is_none() {
+				eprintln!("Warning: Invalid --date-format, timestamps are kept as they are: {}", pattern);
+			}
+		}
 
-		// Collect all rows that are not headers and not the column_width_limits_index row
-		self.data = Some(self.input.clone().expect("REASON").lines()
-			.enumerate()
-			.filter_map(|(i, line)| {
-				// Skip header rows and the column width limits row
-				if (header_start <= i && i < header_end) || i == column_width_limits_index {
-					None // Skip this row
-				} else {
-				// Split the line by the input field separator and collect into Vec<String>
-				let mut row: Vec<String> = line.split(&self.ifs).map(String::from).collect();
-
-				// Check each column for numeric values and format the cell
+		// Highlight rules match the values as parsed, before formatting
+		let highlight_styles = self.highlight_styles(&data_rows, &column_types);
+
+		// Decimal digits of every column with auto precision
+		let decimal_digits = if self.auto_precision {
+			self.decimal_digits(&data_rows, &number_displays, &column_types)
+		} else {
+			vec![None; number_displays.len()]
+		};
+
+		self.data = Some(data_rows.into_iter()
+			.map(|mut row| {
+				let mut numeric_row: Vec<bool> = Vec::new();
+
+				// Format the cells of number columns as numbers, and the others as text
 				for (j, cell) in row.iter_mut().enumerate() {
-					let cell_value = cell.clone();
-			   
-						let mut formatter = TextFormatter::new(Some(cell_value))
-							.set_frame(Frame::NONE)
-							.set_alignment(Alignment::LEFT)
-							.set_decimal_separator(self.decimal_separator)
-							.set_pad_decimal_digits(self.pad_decimal_digits)
-							.set_max_decimal_digits(self.max_decimal_digits)
-							.set_thousand_separator(self.thousand_separator)
-							.set_use_thousand_separator(self.use_thousand_separator)
-							.clone();
-
-						let formatted = formatter.text();
-
-						// Update column width for this cell
-						let width = &formatted.trim().width();
+					// Missing values are shown as the null placeholder, never as numbers
+					if null_values.iter().any(|value| value == cell.trim()) {
+						if let Some(ref null_display) = self.null_display {
+							*cell = null_display.clone();
+						}
+						let width = ansi::width(cell);
 						if j < column_widths.len() {
 							column_widths[j] = column_widths[j]
-								.max(*width)
+								.max(width)
 								.min(column_width_limits.get(j).copied().unwrap_or(usize::MAX));
 						}
+						numeric_row.push(false);
+						continue;
+					}
 
-						// Update numeric_columns
-						if j < numeric_columns.len() {
-							numeric_columns[j] = formatter.is_numeric();
+					let column_type = column_types.get(j).copied().unwrap_or_default();
+					if matches!(column_type, ColumnType::DATE | ColumnType::DATETIME) {
+						if let Some(timestamp) = self.format_timestamp(cell, column_type, epoch_columns.contains(&j)) {
+							*cell = timestamp;
 						}
+					}
+
+					let display = number_displays.get(j).copied().unwrap_or(self.number_display);
+					let mut formatter = self.cell_formatter(cell.clone(), display)
+						.set_decimal_digits(decimal_digits.get(j).copied().flatten())
+						.clone();
+
+					let is_number = column_types.get(j).is_some_and(ColumnType::is_number);
+					let formatted = if is_number { formatter.text() } else { formatter.layout(false) };
 
-						// Replace the original cell with the formatted one
-						*cell = formatted;
+					// Update column width for this cell
+					let width = &ansi::width(formatted.trim());
+					if j < column_widths.len() {
+						column_widths[j] = column_widths[j]
+							.max(*width)
+							.min(column_width_limits.get(j).copied().unwrap_or(usize::MAX));
 					}
-					Some(row) // Include this row in the result
+
+					numeric_row.push(formatter.is_numeric());
+
+					// Replace the original cell with the formatted one
+					*cell = formatted;
 				}
+				numeric_cells.push(numeric_row);
+				row
 			})
 			.collect());
 
-		// Update self with the new column widths and numeric columns
+		// Update self with the new column widths and numeric cells
 		self.data_column_widths = Some(column_widths);
-		self.numeric_columns = Some(numeric_columns);
+		self.numeric_cells = Some(numeric_cells);
+		self.highlight_styles = Some(highlight_styles);
 
 		// Return a reference to the processed data
 		self.data.as_ref().unwrap()
 	}
 
+	/// Creates the formatter that reads and formats a data cell.
+	///
+	/// # Arguments
+	///
+	/// * `cell` - The cell as read from the input.
+	/// * `display` - The number display mode of the cell's column.
+	fn cell_formatter(&self, cell: String, display: NumberDisplay) -> TextFormatter {
+		TextFormatter::new(Some(cell))
+			.set_frame(Frame::NONE)
+			.set_alignment(Alignment::LEFT)
+			.set_decimal_separator(self.decimal_separator)
+			.set_pad_decimal_digits(self.pad_decimal_digits)
+			.set_max_decimal_digits(self.max_decimal_digits)
+			.set_thousand_separator(self.thousand_separator)
+			.set_use_thousand_separator(self.use_thousand_separator)
+			.set_grouping(self.grouping)
+			.set_input_decimal_separator(self.input_decimal_separator)
+			.set_input_thousand_separator(self.input_thousand_separator)
+			.set_number_display(display)
+			.set_currency(self.currency.clone())
+			.set_currency_position(self.currency_position)
+			.set_accounting(self.accounting)
+			.set_plus_sign(self.plus_sign)
+			.set_rounding(self.rounding)
+			.set_strict(self.strict)
+			.set_sig_figs(self.sig_figs)
+			.clone()
+	}
+
+	/// Matches the highlight rules against the data rows.
+	///
+	/// Rules on unknown columns are skipped with a warning. When several rules match, the
+	/// styles of the later ones are applied last.
+	///
+	/// # Returns
+	///
+	/// For every row, the style of the whole row and the style of each cell, empty when no
+	/// rule matches.
+	fn highlight_styles(&mut self, rows: &[Vec<String>], column_types: &[ColumnType]) -> Vec<(String, Vec<String>)> {
+		let rules: Vec<(usize, Highlight)> = self.highlights.clone().into_iter()
+			.filter_map(|rule| {
+				let index = self.column_index(&rule.column);
+				if index.is_none() {
+					eprintln!("Warning: No such column for --highlight: {}", rule.column);
+				}
+				index.map(|index| (index, rule))
+			})
+			.collect();
+
+		let null_values = self.null_values();
+		let nulls: Vec<&str> = null_values.iter().map(String::as_str).collect();
+		let reader = self.cell_formatter(String::new(), NumberDisplay::PLAIN);
+		let number = |text: &str| reader.parse_number(text);
+
+		rows.iter()
+			.map(|row| {
+				let mut row_style = String::new();
+				let mut cell_styles = vec![String::new(); row.len()];
+
+				for (index, rule) in &rules {
+					let cell = row.get(*index).map(String::as_str).unwrap_or("");
+					let column_type = column_types.get(*index).copied().unwrap_or_default();
+					if !rule.matches(cell, column_type, &nulls, &number) {
+						continue;
+					}
+					if rule.row {
+						row_style.push_str(&rule.style);
+					} else if let Some(cell_style) = cell_styles.get_mut(*index) {
+						cell_style.push_str(&rule.style);
+					}
+				}
+
+				(row_style, cell_styles)
+			})
+			.collect()
+	}
+
+	/// Finds the decimal digits each column is padded to with auto precision.
+	///
+	/// This is the fewest digits that show every number of the column once rounded, so all
+	/// of them line up on the decimal separator.
+	///
+	/// # Returns
+	///
+	/// The decimal digits of every column, `None` for columns that are not number columns or
+	/// not shown as plain or percent numbers.
+	fn decimal_digits(&self, rows: &[Vec<String>], number_displays: &[NumberDisplay], column_types: &[ColumnType]) -> Vec<Option<usize>> {
+		let mut decimal_digits: Vec<Option<usize>> = vec![None; number_displays.len()];
+
+		for row in rows {
+			for (j, cell) in row.iter().enumerate().take(decimal_digits.len()) {
+				if !column_types.get(j).is_some_and(ColumnType::is_number) {
+					continue;
+				}
+				let needed = self.cell_formatter(cell.clone(), number_displays[j]).decimal_digits_needed();
+				decimal_digits[j] = decimal_digits[j].max(needed);
+			}
+		}
+
+		decimal_digits
+	}
+
 	/// Calculates and returns the column widths used for formatting table output.
 	///
 	/// This method determines the optimal column widths based on the provided text format:
@@ -668,7 +1767,7 @@ impl TableBuilder {
 	///
 	/// # Example
 	///
-	/// ```rust
+	/// ```ignore
 	/// let column_widths = table.column_widths();
 	/// // column_widths now contains the calculated widths for each column.
 	/// ```
@@ -697,7 +1796,7 @@ impl TableBuilder {
 
 		// Determine the column widths based on the format_text option
 		match self.frame {
-			Frame::TRUNCATE | Frame::CHOP | Frame::NONE => {
+			Frame::TRUNCATE | Frame::CHOP | Frame::MIDDLE | Frame::PATH | Frame::URL | Frame::NONE => {
 				// If TRUNCATE or NONE, set column_widths to the max of header and data widths
 				for i in 0..column_widths.len() {
 					column_widths[i] = header_column_widths[i].max(data_column_widths[i]);
@@ -730,6 +1829,9 @@ impl TableBuilder {
 		let headers = self.headers().clone();
 		let data = self.data().clone();
 		let numeric_columns = self.numeric_columns().clone();
+		let numeric_cells = self.numeric_cells.clone().unwrap_or_default();
+		let colored = self.is_colored();
+		let highlight_styles = self.highlight_styles.clone().unwrap_or_default();
 
 		// Initialize table with custom format
 		let mut table = Table::new();
@@ -740,26 +1842,31 @@ impl TableBuilder {
 			.separator(format::LinePosition::Bottom, format::LineSeparator::new(' ', ' ', ' ', ' ')) // No bottom border
 			.build());
 
+		// Cells of every row with their alignment, padded once all widths are known
+		let mut rows: Vec<Vec<(String, format::Alignment)>> = Vec::new();
+
 		// Handle headers
 		if !headers.is_empty() {
 			for header_line in headers {
 
-				let mut row = Row::empty();
+				let mut row = Vec::new();
 
 				// Iterate over each header cell and add to the row
 				for (i, header_cell) in header_line.iter().enumerate() {
 
-					let (pt_alignment, alignment) = if numeric_columns[i] {
+					let (pt_alignment, alignment) = if numeric_columns.get(i).copied().unwrap_or(false) {
 						(format::Alignment::RIGHT, Alignment::RIGHT)
 					} else {
 						(format::Alignment::LEFT, Alignment::LEFT)
 					};
 
 					let mut formatter = TextFormatter::new(Some(header_cell.to_string()))
-						.set_width(column_widths[i])
+						.set_width(column_widths.get(i).copied().unwrap_or(0))
 						.set_alignment(alignment)
 						.set_frame(self.frame)
 						.set_no_ellipsis(self.no_ellipsis)
+						.set_ellipsis(self.ellipsis.clone())
+						.set_max_lines(self.max_lines)
 						.clone();
 
 					let formatted = formatter.text();
@@ -771,89 +1878,110 @@ impl TableBuilder {
 						formatted
 					};
 
-					let formatted_cell = Cell::new_align(&cell_content, pt_alignment);
+					let cell_content = if colored { ansi::paint(&cell_content, ansi::BOLD) } else { cell_content };
 
 					// Add cell to the row
-					row.add_cell(formatted_cell);
+					row.push((cell_content, pt_alignment));
 
 				}
 
 				// Add the row to the table
-				table.add_row(row);
+				rows.push(row);
 
 			}
 		}
 
 		if !self.no_divider {
-			let mut divider_row = Row::empty();
-
-			// Iterate over each column width
-			for (i, &width) in column_widths.iter().enumerate() {
-				// Repeat the divider character `width` times
-				let divider_cell = self.divider_char.to_string().repeat(width);
-
-				// Prepend self.ofs to the cell, except for the first cell
-				let cell_content = if i > 0 {
-					format!("{}{}", self.ofs, divider_cell)
-				} else {
-					divider_cell
-				};
-
-				// Create a cell and add it to the row
-				divider_row.add_cell(Cell::new(&cell_content));
-			}
-
 			// Add the divider row to the table
-			table.add_row(divider_row);
+			rows.push(self.divider_row(&column_widths));
 		}
 
+		// Footer rows start after this many data rows
+		let footer_start = data.len().saturating_sub(self.footer_count);
 
 		// Handle data (you can implement data row filling in a similar way)
 		if !data.is_empty() {
-			for data_row in data {
-				let mut row = prettytable::Row::empty();
+			for (r, data_row) in data.into_iter().enumerate() {
+				// Separate footer rows from the data with another divider
+				if r == footer_start && self.footer_count > 0 && !self.no_divider {
+					rows.push(self.divider_row(&column_widths));
+				}
+
+				let mut row = Vec::new();
 
 				// Fill each cell in the data row
 				for (i, data_cell) in data_row.iter().enumerate() {
 
-					let (pt_alignment, alignment) = if numeric_columns[i] {
+					let (pt_alignment, alignment) = if numeric_columns.get(i).copied().unwrap_or(false) {
 						(format::Alignment::RIGHT, Alignment::RIGHT)
 					} else {
 						(format::Alignment::LEFT, Alignment::LEFT)
 					};
 
+					// Cells were already formatted by data(), only lay them out
+					let is_numeric = numeric_cells.get(r).and_then(|cells| cells.get(i)).copied().unwrap_or(false);
 					let mut formatter = TextFormatter::new(Some(data_cell.to_string()))
-						.set_width(column_widths[i])
+						.set_width(column_widths.get(i).copied().unwrap_or(0))
 						.set_alignment(alignment)
 						.set_frame(self.frame)
 						.set_no_ellipsis(self.no_ellipsis)
-						.set_pad_decimal_digits(self.pad_decimal_digits)
-						.set_max_decimal_digits(self.max_decimal_digits)
-						.set_decimal_separator(self.decimal_separator)
-						.set_use_thousand_separator(self.use_thousand_separator)
-						.set_thousand_separator(self.thousand_separator)
+						.set_ellipsis(self.ellipsis.clone())
+						.set_max_lines(self.max_lines)
 						.clone();
 
-					let formatted = formatter.text();
+					let formatted = formatter.layout(is_numeric);
+
+					// Style the cell, and its separator like the rest of the row
+					let (ofs, formatted) = if colored {
+						let (row_highlight, cell_highlights) = highlight_styles.get(r).cloned().unwrap_or_default();
+						let stripe = if r % 2 == 1 && r < footer_start { ansi::STRIPE } else { "" };
+						let row_style = format!("{}{}", stripe, row_highlight);
+						let cell_style = cell_highlights.get(i).cloned().unwrap_or_default();
+						let width = column_widths.get(i).copied().unwrap_or(0);
+						(
+							self.paint_cell(&self.ofs, 0, false, &row_style, ""),
+							self.paint_cell(&formatted, width, is_numeric, &row_style, &cell_style),
+						)
+					} else {
+						(self.ofs.clone(), formatted)
+					};
 
 					// Prepend self.ofs to the cell, except for the first cell
 					let cell_content = if i > 0 {
-						format!("{}{}", self.ofs, formatted)
+						format!("{}{}", ofs, formatted)
 					} else {
 						formatted
 					};
 
-					let formatted_cell = Cell::new_align(&cell_content, pt_alignment);
-
 					// Add cell to the row
-					row.add_cell(formatted_cell);
+					row.push((cell_content, pt_alignment));
 				}
 
 				// Add the data row to the table
-				table.add_row(row);
+				rows.push(row);
+			}
+		}
+
+		// Pad the cells to the widest line of their column, not counting escape sequences
+		let mut widths: Vec<usize> = Vec::new();
+		for row in &rows {
+			widths.resize(widths.len().max(row.len()), 0);
+			for (i, (content, _)) in row.iter().enumerate() {
+				widths[i] = content.lines().map(ansi::width).fold(widths[i], usize::max);
 			}
 		}
 
+		for row in rows {
+			let height = row.iter().map(|(content, _)| content.lines().count()).max().unwrap_or(0).max(1);
+			table.add_row(Row::new(row.iter()
+				.enumerate()
+				.map(|(i, (content, alignment))| {
+					let padded = pad_cell(content, widths[i], height, *alignment, i + 1 == widths.len());
+					Cell::new_align(&padded, *alignment)
+				})
+				.collect()));
+		}
+
         // Cache the table
         self.table = Some(table);
 
@@ -861,4 +1989,187 @@ impl TableBuilder {
         self.table.as_mut().unwrap()
 
 	}
+
+	/// Whether plain output is styled, following `color`.
+	///
+	/// With `Color::AUTO`, output is styled when standard output is a terminal and `NO_COLOR`
+	/// is unset or empty.
+	pub fn is_colored(&self) -> bool {
+		match self.color {
+			Color::ALWAYS => true,
+			Color::NEVER => false,
+			Color::AUTO => std::env::var_os("NO_COLOR").is_none_or(|value| value.is_empty())
+				&& std::io::stdout().is_terminal(),
+		}
+	}
+
+	/// Styles a laid out data cell with the style of its row, then red for negative numbers,
+	/// then the style of the cell.
+	///
+	/// Styled cells are padded to `width` first, so backgrounds run across the whole column.
+	fn paint_cell(&self, content: &str, width: usize, is_numeric: bool, row_style: &str, cell_style: &str) -> String {
+		let is_negative = is_numeric && {
+			let visible = ansi::strip(content);
+			let visible = visible.trim_start();
+			visible.starts_with('-') || visible.starts_with('(')
+		};
+
+		let style = format!("{}{}{}", row_style, if is_negative { ansi::RED } else { "" }, cell_style);
+		if style.is_empty() {
+			return content.to_string();
+		}
+
+		let content = content.split('\n')
+			.map(|line| format!("{}{}", line, " ".repeat(width.saturating_sub(ansi::width(line)))))
+			.collect::<Vec<_>>()
+			.join("\n");
+
+		ansi::paint(&content, &style)
+	}
+
+	/// Builds a divider row made of `divider_char`, one left-aligned cell per column.
+	fn divider_row(&self, column_widths: &[usize]) -> Vec<(String, format::Alignment)> {
+		let mut divider_row = Vec::new();
+
+		// Iterate over each column width
+		for (i, &width) in column_widths.iter().enumerate() {
+			// Repeat the divider character `width` times
+			let divider_cell = self.divider_char.to_string().repeat(width);
+
+			// Prepend self.ofs to the cell, except for the first cell
+			let cell_content = if i > 0 {
+				format!("{}{}", self.ofs, divider_cell)
+			} else {
+				divider_cell
+			};
+
+			let cell_content = if self.is_colored() { ansi::paint(&cell_content, ansi::DIM) } else { cell_content };
+
+			// Add the cell to the row
+			divider_row.push((cell_content, format::Alignment::LEFT));
+		}
+
+		divider_row
+	}
+}
+
+/// Pads a plain text cell to `height` lines of `width` display columns.
+///
+/// Cells are padded the way `prettytable` pads them, except that escape sequences take no
+/// width: right-aligned lines are padded on the left, left-aligned lines on the right, but not
+/// in the last column.
+fn pad_cell(content: &str, width: usize, height: usize, alignment: format::Alignment, is_last: bool) -> String {
+	content.lines()
+		.chain(std::iter::repeat(""))
+		.take(height)
+		.map(|line| {
+			let padding = " ".repeat(width.saturating_sub(ansi::width(line)));
+			match alignment {
+				format::Alignment::RIGHT => format!("{}{}", padding, line),
+				_ if is_last => line.to_string(),
+				_ => format!("{}{}", line, padding),
+			}
+		})
+		.collect::<Vec<_>>()
+		.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// A table with a short row on line 2 and a long row on line 4, after a blank line.
+	fn ragged(ragged: Ragged) -> TableBuilder {
+		let mut table = TableBuilder::new(Some("a b c\n1 2\n\n3 4 5 6 7".to_string()));
+		table.set_ragged(ragged).set_null_display(Some("?".to_string()));
+		table
+	}
+
+	#[test]
+	fn pad_keeps_every_field() {
+		let mut table = ragged(Ragged::PAD);
+		assert_eq!(table.column_count(), 5);
+		assert_eq!(table.data().clone(), [["1", "2", "?", "?", "?"], ["3", "4", "5", "6", "7"]]);
+		assert!(table.validate().is_ok());
+	}
+
+	#[test]
+	fn truncate_drops_extra_fields() {
+		let mut table = ragged(Ragged::TRUNCATE);
+		assert_eq!(table.column_count(), 3);
+		assert_eq!(table.data().clone(), [["1", "2", "?"], ["3", "4", "5"]]);
+		assert!(table.validate().is_ok());
+	}
+
+	#[test]
+	fn merge_tail_joins_extra_fields() {
+		let mut table = ragged(Ragged::MERGE_TAIL);
+		assert_eq!(table.column_count(), 3);
+		assert_eq!(table.data().clone(), [["1", "2", "?"], ["3", "4", "5 6 7"]]);
+		assert!(table.validate().is_ok());
+	}
+
+	#[test]
+	fn error_names_the_ragged_lines() {
+		let error = ragged(Ragged::ERROR).validate().unwrap_err().to_string();
+		assert_eq!(error, "Ragged rows:\n  line 2: 2 fields, expected 3\n  line 4: 5 fields, expected 3");
+		assert!(TableBuilder::new(Some("a b\n1 2".to_string())).set_ragged(Ragged::ERROR).validate().is_ok());
+	}
+	fn highlight(rule: &str) -> Highlight {
+		rule.parse().unwrap()
+	}
+
+	fn matches(rule: &str, cell: &str, column_type: ColumnType) -> bool {
+		highlight(rule).matches(cell, column_type, &infer::NULL_VALUES, &|text: &str| Decimal::parse(text, false))
+	}
+
+	#[test]
+	fn highlight_rules_are_parsed() {
+		let rule = highlight("status=FAIL:row bold");
+		assert!(rule.comparison == Comparison::EQ && rule.value == "FAIL" && rule.row);
+		assert_eq!(rule.style, "\x1b[1m");
+
+		let rule = highlight("2 >= 5:red");
+		assert!(rule.comparison == Comparison::GE && rule.value == "5" && !rule.row);
+		assert_eq!(rule.style, "\x1b[31m");
+
+		assert!(highlight("name~x:row").style.is_empty());
+		assert_eq!("a=1:blink".parse::<Highlight>().err().as_deref(), Some("Invalid style: blink"));
+		assert!("latency>500".parse::<Highlight>().is_err());
+		assert!("latency:red".parse::<Highlight>().is_err());
+	}
+
+	#[test]
+	fn numeric_cells_compare_by_value() {
+		assert!(matches("n=500:red", "500.0", ColumnType::DECIMAL));
+		assert!(matches("n!=500:red", "501", ColumnType::INTEGER));
+		assert!(matches("n<500:red", "99", ColumnType::INTEGER));
+		assert!(!matches("n<500:red", "500", ColumnType::INTEGER));
+		assert!(matches("n<=500:red", "500", ColumnType::INTEGER));
+		assert!(matches("n>500:red", "1500", ColumnType::INTEGER));
+		assert!(!matches("n>500:red", "99", ColumnType::INTEGER));
+		assert!(matches("n>=500:red", "500", ColumnType::INTEGER));
+		assert!(matches("n~50:red", "1500", ColumnType::INTEGER));
+		assert!(matches("n>1m:red", "90s", ColumnType::DURATION));
+	}
+
+	#[test]
+	fn text_cells_compare_by_characters() {
+		assert!(matches("s=FAIL:red", "FAIL", ColumnType::TEXT));
+		assert!(!matches("s=FAIL:red", "fail", ColumnType::TEXT));
+		assert!(matches("s!=FAIL:red", "OK", ColumnType::TEXT));
+		assert!(matches("s<banana:red", "apple", ColumnType::TEXT));
+		assert!(matches("s<=apple:red", "apple", ColumnType::TEXT));
+		assert!(matches("s>banana:red", "cherry", ColumnType::TEXT));
+		assert!(matches("s>=cherry:red", "cherry", ColumnType::TEXT));
+		assert!(matches("s~AI:red", "\x1b[1mFAIL\x1b[0m", ColumnType::TEXT));
+	}
+
+	#[test]
+	fn null_cells_only_match_by_text() {
+		assert!(!matches("n>0:red", "NULL", ColumnType::INTEGER));
+		assert!(!matches("n<0:red", "NULL", ColumnType::INTEGER));
+		assert!(matches("n=NULL:red", "NULL", ColumnType::INTEGER));
+		assert!(matches("n!=0:red", "-", ColumnType::INTEGER));
+	}
 }
@@ -1,9 +1,12 @@
 use clap::{Args, ValueEnum};
+use crate::ansi;
+use crate::decimal::{Decimal, Rounding};
 use crate::input;
+use crate::paragraph;
 use std::fmt;
 use std::str::FromStr;
-use textwrap;
-use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
 
 /// Cleans the provided text by trimming whitespace and removing empty lines.
 ///
@@ -23,7 +26,7 @@ use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 ///
 /// # Example
 ///
-/// ```
+/// ```ignore
 /// let cleaned_text = clean(Some("  line1  \n\n  line2  \n  ")); // Returns "line1\nline2"
 /// let empty_text = clean(None); // Returns ""
 /// ```
@@ -39,7 +42,7 @@ pub fn clean(text: Option<&str>) -> String {
 ///
 /// * `text` - A string slice containing the lines to be right-aligned.
 /// * `width` - An optional width to which the lines should be aligned. If `None` or less than or equal to zero,
-///			 the width of the longest line will be used.
+///   the width of the longest line will be used.
 ///
 /// # Returns
 ///
@@ -55,7 +58,7 @@ pub fn right(text: Option<&str>, width: Option<usize>) -> String {
 ///
 /// # Parameters
 /// - `text`: An optional string slice that contains the text to be formatted.
-/// If `None` is provided, the function returns an empty string.
+///   If `None` is provided, the function returns an empty string.
 ///
 /// # Returns
 /// A `String` containing the left-aligned formatted text.
@@ -69,21 +72,65 @@ pub fn left(text: Option<&str>) -> String {
 ///
 /// # Parameters
 /// - `text`: An optional string slice that contains the text to be wrapped.
-/// If `None` is provided, the function returns an empty string.
+///   If `None` is provided, the function returns an empty string.
 /// - `width`: The maximum width for wrapping the text.
+/// - `reflow`: An optional flag to wrap paragraphs as a whole rather than each line.
+/// - `comment`: An optional flag to keep comment and quote prefixes on every line.
 ///
 /// # Returns
 /// A `String` containing the wrapped text at the specified width.
-pub fn wrap(text: Option<&str>, width: Option<usize>) -> String {
+pub fn wrap(text: Option<&str>, width: Option<usize>, reflow: Option<bool>, comment: Option<bool>) -> String {
     let text_string = text.map(|s| s.to_string());       // Convert Option<&str> to Option<String>
     let mut formatter = TextFormatter::new(text_string); // Pass the Option<String>
     
     // Set the width, only if it is provided
     if let Some(w) = width { formatter.set_width(w); }
 
+    // Set reflow, only if it is provided
+    if let Some(r) = reflow { formatter.set_reflow(r); }
+
+    // Set comment, only if it is provided
+    if let Some(c) = comment { formatter.set_comment(c); }
+
 	formatter.wrap()
 }
 
+/// Wraps the provided text to the specified width and justifies it.
+///
+/// # Parameters
+/// - `text`: An optional string slice that may contain the text to be justified.
+/// - `width`: The width every line but the last of a paragraph is stretched to.
+/// - `last_line`: An optional alignment of the last line of each paragraph, left by default.
+/// - `reflow`: An optional flag to wrap paragraphs as a whole rather than each line.
+/// - `comment`: An optional flag to keep comment and quote prefixes on every line.
+///
+/// # Returns
+/// A `String` containing the justified text.
+pub fn justify(
+	text: Option<&str>,
+	width: Option<usize>,
+	last_line: Option<Alignment>,
+	reflow: Option<bool>,
+	comment: Option<bool>,
+) -> String {
+    let text_string = text.map(|s| s.to_string());       // Convert Option<&str> to Option<String>
+    let mut formatter = TextFormatter::new(text_string); // Pass the Option<String>
+    
+    // Set the width, only if it is provided
+    if let Some(w) = width { formatter.set_width(w); }
+
+    // Set reflow, only if it is provided
+    if let Some(r) = reflow { formatter.set_reflow(r); }
+
+    // Set comment, only if it is provided
+    if let Some(c) = comment { formatter.set_comment(c); }
+
+    // Set the alignment of last lines, only if it is provided
+    if let Some(a) = last_line { formatter.set_last_line(a); }
+
+	formatter.set_alignment(Alignment::JUSTIFY).wrap()
+}
+
 /// Centers the provided text within the specified width.
 ///
 /// # Parameters
@@ -111,6 +158,8 @@ pub fn center(text: Option<&str>, width: Option<usize>) -> String {
 /// # Parameters
 /// - `text`: An optional string slice that may contain the text to be truncated.
 /// - `width`: The maximum width the text should occupy.
+/// - `no_ellipsis`: An optional flag that leaves the ellipsis out of truncated text.
+/// - `ellipsis`: An optional marker for truncated text, `{}` stands for the number of hidden characters.
 /// - `frame`: An optional `Frame` type that defines how the text should be truncated (e.g., `CHOP`).
 ///
 /// # Returns
@@ -119,6 +168,7 @@ pub fn truncate(
 	text: Option<&str>,
 	width: Option<usize>,
 	no_ellipsis: Option<bool>,
+	ellipsis: Option<&str>,
 	frame: Option<Frame>,
 ) -> String {
     // Convert Option<&str> to Option<String>
@@ -136,21 +186,41 @@ pub fn truncate(
     // Set no_ellipsis, only if it is provided
     if let Some(n) = no_ellipsis { formatter.set_no_ellipsis(n); }
     
+    // Set the ellipsis, only if it is provided
+    if let Some(n) = ellipsis { formatter.set_ellipsis(n.to_string()); }
+    
     // Perform truncation and return the result
     formatter.truncate()
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn text(
 	text: Option<&str>,
 	width: Option<usize>,
 	frame: Option<Frame>,
 	no_ellipsis: Option<bool>,
+	ellipsis: Option<&str>,
+	max_lines: Option<usize>,
+	reflow: Option<bool>,
+	comment: Option<bool>,
 	pad_decimal_digits: Option<bool>,
 	max_decimal_digits: Option<usize>,
 	decimal_separator: Option<char>,
 	use_thousand_separator: Option<bool>,
 	thousand_separator: Option<char>,
+	grouping: Option<Grouping>,
+	input_decimal_separator: Option<char>,
+	input_thousand_separator: Option<char>,
+	number_display: Option<NumberDisplay>,
+	currency: Option<&str>,
+	currency_position: Option<CurrencyPosition>,
+	accounting: Option<bool>,
+	plus_sign: Option<bool>,
+	rounding: Option<Rounding>,
+	strict: Option<bool>,
+	sig_figs: Option<usize>,
 	alignment: Option<Alignment>,
+	last_line: Option<Alignment>,
 ) -> String {
     // Convert Option<&str> to Option<String>
     let text_string = text.map(|s| s.to_string());
@@ -167,6 +237,18 @@ pub fn text(
     // Set no_ellipsis, only if it is provided
     if let Some(n) = no_ellipsis { formatter.set_no_ellipsis(n); }
     
+    // Set the ellipsis, only if it is provided
+    if let Some(n) = ellipsis { formatter.set_ellipsis(n.to_string()); }
+    
+    // Set the maximum number of wrapped lines
+    formatter.set_max_lines(max_lines);
+    
+    // Set reflow, only if it is provided
+    if let Some(r) = reflow { formatter.set_reflow(r); }
+    
+    // Set comment, only if it is provided
+    if let Some(c) = comment { formatter.set_comment(c); }
+    
     // Set pad_decimal_digits, only if it is provided
     if let Some(n) = pad_decimal_digits { formatter.set_pad_decimal_digits(n); }
     
@@ -182,31 +264,66 @@ pub fn text(
     // Set thousand_separator, only if it is provided
     if let Some(n) = thousand_separator { formatter.set_thousand_separator(n); }
     
+    // Set grouping, only if it is provided
+    if let Some(n) = grouping { formatter.set_grouping(n); }
+    
+    // Set the input separators, they default to the output ones
+    formatter.set_input_decimal_separator(input_decimal_separator);
+    formatter.set_input_thousand_separator(input_thousand_separator);
+    
+    // Set number_display, only if it is provided
+    if let Some(n) = number_display { formatter.set_number_display(n); }
+    
+    // Set the currency symbol, only if it is provided
+    formatter.set_currency(currency.map(String::from));
+    
+    // Set currency_position, only if it is provided
+    if let Some(n) = currency_position { formatter.set_currency_position(n); }
+    
+    // Set accounting, only if it is provided
+    if let Some(n) = accounting { formatter.set_accounting(n); }
+    
+    // Set plus_sign, only if it is provided
+    if let Some(n) = plus_sign { formatter.set_plus_sign(n); }
+    
+    // Set rounding, only if it is provided
+    if let Some(n) = rounding { formatter.set_rounding(n); }
+    
+    // Set strict, only if it is provided
+    if let Some(n) = strict { formatter.set_strict(n); }
+    
+    // Set the significant figures, only if they are provided
+    formatter.set_sig_figs(sig_figs);
+    
     // Set alignment, only if it is provided
     if let Some(n) = alignment { formatter.set_alignment(n); }
     
+    // Set the alignment of last justified lines, only if it is provided
+    if let Some(n) = last_line { formatter.set_last_line(n); }
+    
     // Perform truncation and return the result
     formatter.text()
 }
 
-#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+#[derive(Copy, Clone, Default, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
 pub enum Frame {
     /// Truncate the end of text to fit the width.
+    #[default]
     TRUNCATE,
     /// Chop off the start of text to fit the width.
     CHOP,
+    /// Cut out the middle of text to fit the width.
+    MIDDLE,
+    /// Drop whole directory components of a path, then cut out the middle.
+    PATH,
+    /// Keep the scheme, host and last path segment of a URL, then cut out the middle.
+    URL,
     /// Wrap the text to fit the width.
     WRAP,
     /// Leave the text unchanged.
     NONE,
 }
 
-impl Default for Frame {
-    fn default() -> Self {
-        Frame::TRUNCATE
-    }
-}
-
 impl FromStr for Frame {
 	type Err = String;
 
@@ -214,6 +331,9 @@ impl FromStr for Frame {
 		match text.to_uppercase().as_str() {
 			"TRUNCATE" => Ok(Frame::TRUNCATE),
 			"CHOP" => Ok(Frame::CHOP),
+			"MIDDLE" => Ok(Frame::MIDDLE),
+			"PATH" => Ok(Frame::PATH),
+			"URL" => Ok(Frame::URL),
 			"WRAP" => Ok(Frame::WRAP),
 			"NONE" => Ok(Frame::NONE),
 			_ => Err(format!("Invalid frame type: {}", text)),
@@ -226,6 +346,9 @@ impl fmt::Display for Frame {
 		match self {
 			Frame::TRUNCATE => write!(f, "TRUNCATE"),
 			Frame::CHOP => write!(f, "CHOP"),
+			Frame::MIDDLE => write!(f, "MIDDLE"),
+			Frame::PATH => write!(f, "PATH"),
+			Frame::URL => write!(f, "URL"),
 			Frame::WRAP => write!(f, "WRAP"),
 			Frame::NONE => write!(f, "NONE"),
 		}
@@ -247,6 +370,9 @@ pub enum Alignment {
 	/// Right align the text.
 	RIGHT,
 	
+	/// Stretch the spaces between words so wrapped lines fill the width.
+	JUSTIFY,
+	
 	/// No alignment; do not apply any specific alignment.
 	NONE,
 }
@@ -256,11 +382,12 @@ impl FromStr for Alignment {
 
 	fn from_str(text: &str) -> Result<Alignment, Self::Err> {
 		match text.to_uppercase().as_str() {
-			"AUTO"   => Ok(Alignment::AUTO),
-			"CENTER" => Ok(Alignment::CENTER),
-			"LEFT"   => Ok(Alignment::LEFT),
-			"RIGHT"  => Ok(Alignment::RIGHT),
-			"NONE"   => Ok(Alignment::NONE),
+			"AUTO"    => Ok(Alignment::AUTO),
+			"CENTER"  => Ok(Alignment::CENTER),
+			"LEFT"    => Ok(Alignment::LEFT),
+			"RIGHT"   => Ok(Alignment::RIGHT),
+			"JUSTIFY" => Ok(Alignment::JUSTIFY),
+			"NONE"    => Ok(Alignment::NONE),
 			_ => Err(format!("Invalid frame type: {}", text)),
 		}
 	}
@@ -269,13 +396,515 @@ impl FromStr for Alignment {
 impl fmt::Display for Alignment {
 	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
 		match self {
-			Alignment::AUTO   => write!(f, "AUTO"  ),
-			Alignment::CENTER => write!(f, "CENTER"),
-			Alignment::LEFT   => write!(f, "LEFT"  ),
-			Alignment::RIGHT  => write!(f, "RIGHT" ),
-			Alignment::NONE   => write!(f, "NONE"  ),
+			Alignment::AUTO    => write!(f, "AUTO"   ),
+			Alignment::CENTER  => write!(f, "CENTER" ),
+			Alignment::LEFT    => write!(f, "LEFT"   ),
+			Alignment::RIGHT   => write!(f, "RIGHT"  ),
+			Alignment::JUSTIFY => write!(f, "JUSTIFY"),
+			Alignment::NONE    => write!(f, "NONE"   ),
+		}
+	}
+}
+
+#[derive(Copy, Clone, Default, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+pub enum NumberDisplay {
+	/// Plain decimal number.
+	#[default]
+	PLAIN,
+
+	/// Byte count with binary units: `1.5 KiB`.
+	BYTES,
+
+	/// SI prefixes: `1.5k`, `12M`, `250m`.
+	SI,
+
+	/// Number of seconds as a duration: `3h 12m`.
+	DURATION,
+
+	/// Fraction as a percentage, multiplied by 100: `12.5%`.
+	PERCENT,
+
+	/// Scientific notation: `1.23e4`.
+	SCIENTIFIC,
+
+	/// Engineering notation, with exponents in multiples of three: `12.3e3`.
+	ENGINEERING,
+}
+
+impl FromStr for NumberDisplay {
+	type Err = String;
+
+	fn from_str(text: &str) -> Result<NumberDisplay, Self::Err> {
+		match text.to_uppercase().as_str() {
+			"PLAIN"    => Ok(NumberDisplay::PLAIN),
+			"BYTES"    => Ok(NumberDisplay::BYTES),
+			"SI"       => Ok(NumberDisplay::SI),
+			"DURATION" => Ok(NumberDisplay::DURATION),
+			"PERCENT"     => Ok(NumberDisplay::PERCENT),
+			"SCIENTIFIC"  => Ok(NumberDisplay::SCIENTIFIC),
+			"ENGINEERING" => Ok(NumberDisplay::ENGINEERING),
+			_ => Err(format!("Invalid number display: {}", text)),
+		}
+	}
+}
+
+impl fmt::Display for NumberDisplay {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			NumberDisplay::PLAIN    => write!(f, "PLAIN"   ),
+			NumberDisplay::BYTES    => write!(f, "BYTES"   ),
+			NumberDisplay::SI       => write!(f, "SI"      ),
+			NumberDisplay::DURATION => write!(f, "DURATION"),
+			NumberDisplay::PERCENT     => write!(f, "PERCENT"    ),
+			NumberDisplay::SCIENTIFIC  => write!(f, "SCIENTIFIC" ),
+			NumberDisplay::ENGINEERING => write!(f, "ENGINEERING"),
+		}
+	}
+}
+
+#[derive(Copy, Clone, Default, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+pub enum CurrencyPosition {
+	/// Currency symbol before the number: `$1.50`.
+	#[default]
+	PREFIX,
+
+	/// Currency symbol after the number: `1,50 €`.
+	SUFFIX,
+}
+
+impl FromStr for CurrencyPosition {
+	type Err = String;
+
+	fn from_str(text: &str) -> Result<CurrencyPosition, Self::Err> {
+		match text.to_uppercase().as_str() {
+			"PREFIX" => Ok(CurrencyPosition::PREFIX),
+			"SUFFIX" => Ok(CurrencyPosition::SUFFIX),
+			_ => Err(format!("Invalid currency position: {}", text)),
+		}
+	}
+}
+
+impl fmt::Display for CurrencyPosition {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			CurrencyPosition::PREFIX => write!(f, "PREFIX"),
+			CurrencyPosition::SUFFIX => write!(f, "SUFFIX"),
+		}
+	}
+}
+
+#[derive(Copy, Clone, Default, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+pub enum Grouping {
+	/// Group integer digits by three: `123,456,789`.
+	#[default]
+	THOUSANDS,
+
+	/// Group the last three integer digits, then by two: `12,34,56,789`.
+	INDIAN,
+}
+
+impl FromStr for Grouping {
+	type Err = String;
+
+	fn from_str(text: &str) -> Result<Grouping, Self::Err> {
+		match text.to_uppercase().as_str() {
+			"THOUSANDS" => Ok(Grouping::THOUSANDS),
+			"INDIAN"    => Ok(Grouping::INDIAN),
+			_ => Err(format!("Invalid grouping: {}", text)),
+		}
+	}
+}
+
+impl fmt::Display for Grouping {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Grouping::THOUSANDS => write!(f, "THOUSANDS"),
+			Grouping::INDIAN    => write!(f, "INDIAN"   ),
+		}
+	}
+}
+
+/// Number formatting presets, setting the decimal separator, the thousand separator and the
+/// digit grouping together.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+#[allow(non_camel_case_types)]
+pub enum Locale {
+	/// `1,234,567.89`
+	#[value(name = "en_US")]
+	EN_US,
+	/// `1,234,567.89`
+	#[value(name = "en_GB")]
+	EN_GB,
+	/// `12,34,567.89`
+	#[value(name = "en_IN")]
+	EN_IN,
+	/// `12,34,567.89`
+	#[value(name = "hi_IN")]
+	HI_IN,
+	/// `1.234.567,89`
+	#[value(name = "de_DE")]
+	DE_DE,
+	/// `1'234'567.89`
+	#[value(name = "de_CH")]
+	DE_CH,
+	/// `1 234 567,89`, grouped with a narrow no-break space
+	#[value(name = "fr_FR")]
+	FR_FR,
+	/// `1.234.567,89`
+	#[value(name = "it_IT")]
+	IT_IT,
+	/// `1.234.567,89`
+	#[value(name = "es_ES")]
+	ES_ES,
+	/// `1.234.567,89`
+	#[value(name = "nl_NL")]
+	NL_NL,
+	/// `1.234.567,89`
+	#[value(name = "pt_BR")]
+	PT_BR,
+	/// `1 234 567,89`, grouped with a no-break space
+	#[value(name = "ru_RU")]
+	RU_RU,
+	/// `1 234 567,89`, grouped with a no-break space
+	#[value(name = "sv_SE")]
+	SV_SE,
+	/// `1 234 567,89`, grouped with a no-break space
+	#[value(name = "pl_PL")]
+	PL_PL,
+	/// `1,234,567.89`
+	#[value(name = "ja_JP")]
+	JA_JP,
+	/// `1,234,567.89`
+	#[value(name = "zh_CN")]
+	ZH_CN,
+}
+
+impl Locale {
+	/// The character between the integer and fractional digits.
+	pub fn decimal_separator(&self) -> char {
+		match self {
+			Locale::EN_US | Locale::EN_GB | Locale::EN_IN | Locale::HI_IN
+			| Locale::DE_CH | Locale::JA_JP | Locale::ZH_CN => '.',
+			_ => ',',
+		}
+	}
+
+	/// The character between groups of integer digits.
+	pub fn thousand_separator(&self) -> char {
+		match self {
+			Locale::DE_DE | Locale::IT_IT | Locale::ES_ES | Locale::NL_NL | Locale::PT_BR => '.',
+			Locale::DE_CH => '\'',
+			Locale::FR_FR => '\u{202F}', // Narrow no-break space
+			Locale::RU_RU | Locale::SV_SE | Locale::PL_PL => '\u{A0}', // No-break space
+			_ => ',',
+		}
+	}
+
+	/// How integer digits are grouped.
+	pub fn grouping(&self) -> Grouping {
+		match self {
+			Locale::EN_IN | Locale::HI_IN => Grouping::INDIAN,
+			_ => Grouping::THOUSANDS,
+		}
+	}
+}
+
+impl FromStr for Locale {
+	type Err = String;
+
+	fn from_str(text: &str) -> Result<Locale, Self::Err> {
+		<Locale as ValueEnum>::from_str(&text.replace('-', "_"), true)
+			.map_err(|_| format!("Invalid locale: {}", text))
+	}
+}
+
+impl fmt::Display for Locale {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self.to_possible_value() {
+			Some(value) => write!(f, "{}", value.get_name()),
+			None => Ok(()),
+		}
+	}
+}
+
+/// Inserts `separator` between groups of digits in `integer`, following `grouping`.
+///
+/// A leading sign is kept in front of the first group.
+///
+/// # Arguments
+///
+/// * `integer` - The integer digits, optionally preceded by `-` or `+`.
+/// * `separator` - The character placed between groups.
+/// * `grouping` - How the digits are grouped.
+///
+/// # Returns
+///
+/// A `String` with the grouped digits.
+pub fn group_digits(integer: &str, separator: char, grouping: Grouping) -> String {
+	let (sign, digits) = match integer.strip_prefix(['-', '+']) {
+		Some(digits) => (&integer[..1], digits),
+		None => ("", integer),
+	};
+
+	let mut grouped: Vec<char> = Vec::new();
+	for (i, ch) in digits.chars().rev().enumerate() {
+		let boundary = match grouping {
+			Grouping::THOUSANDS => i > 0 && i % 3 == 0,
+			Grouping::INDIAN    => i == 3 || (i > 3 && (i - 3) % 2 == 0),
+		};
+		if boundary {
+			grouped.push(separator);
+		}
+		grouped.push(ch);
+	}
+	grouped.reverse();
+
+	format!("{}{}", sign, grouped.into_iter().collect::<String>())
+}
+
+/// Returns the longest start of `line` that fits in `width` display columns.
+///
+/// The line is cut between extended grapheme clusters, so emoji sequences, flags and letters
+/// with combining accents are kept whole. Escape sequences take no width and are kept up to
+/// the cut, the styles they leave open are closed with [`ansi::close`].
+pub fn fit_start(line: &str, width: usize) -> &str {
+	let mut used = 0;
+	let mut offset = 0;
+	for segment in ansi::segments(line) {
+		match segment {
+			ansi::Segment::Escape(escape) => offset += escape.len(),
+			ansi::Segment::Text(text) => {
+				for (i, grapheme) in text.grapheme_indices(true) {
+					used += grapheme.width();
+					if used > width {
+						return &line[..offset + i];
+					}
+				}
+				offset += text.len();
+			},
+		}
+	}
+	line
+}
+
+/// Returns the longest end of `line` that fits in `width` display columns, cut between
+/// grapheme clusters like [`fit_start`].
+///
+/// Escape sequences after the cut are kept, the styles opened before it are not.
+pub fn fit_end(line: &str, width: usize) -> &str {
+	let mut used = 0;
+	let mut offset = line.len();
+	for segment in ansi::segments(line).into_iter().rev() {
+		match segment {
+			ansi::Segment::Escape(escape) => offset -= escape.len(),
+			ansi::Segment::Text(text) => {
+				let start = offset - text.len();
+				for (i, grapheme) in text.grapheme_indices(true).rev() {
+					used += grapheme.width();
+					if used > width {
+						return &line[start + i + grapheme.len()..];
+					}
+				}
+				offset = start;
+			},
+		}
+	}
+	line
+}
+
+/// Returns the columns that `text` falls short of `width`, left when a wide character
+/// straddles the cut.
+fn shortfall(text: &str, width: usize) -> usize {
+	width.saturating_sub(ansi::width(text))
+}
+
+/// Cuts out the middle of `line` so that it takes exactly `width` display columns with
+/// `ellipsis` in place of the middle: `/usr/loc...bin/tool`.
+///
+/// The start gets the extra column when the room left is odd. Columns left by wide characters
+/// at either cut are filled with spaces next to the ellipsis, and their number is returned with
+/// the line. Styles that were open at the cut are reopened for the end of the line.
+fn truncate_middle(line: &str, width: usize, ellipsis: &str) -> (String, usize) {
+	let room = width.saturating_sub(ansi::width(ellipsis));
+	let (start_room, end_room) = (room - room / 2, room / 2);
+	let start = fit_start(line, start_room);
+	let end = fit_end(&line[start.len()..], end_room);
+	let reopen = ansi::style_at_end(&line[..line.len() - end.len()]).open();
+	let (start_gap, end_gap) = (shortfall(start, start_room), shortfall(end, end_room));
+
+	let truncated = format!(
+		"{}{}{}{}{}{}",
+		ansi::close(start), " ".repeat(start_gap), ellipsis, " ".repeat(end_gap), reopen, end,
+	);
+	(ansi::close(&truncated), start_gap + end_gap)
+}
+
+/// Drops whole components from the middle of a path or URL until it fits in `width` display
+/// columns, putting `ellipsis` in their place: `/usr/.../bin/tool`.
+///
+/// The components lie between `head`, the bytes kept at the start, and the query or fragment
+/// of a URL, which stays with the last component. When even the last component alone does not
+/// fit, the start is kept with as much of the end as fits after the ellipsis:
+/// `https://example.com/...tml?x=1`. Only when the start itself does not fit is the middle of
+/// the shortest form cut out.
+///
+/// The result is padded with spaces to exactly `width` columns, and their number is returned
+/// with it.
+fn elide_components(line: &str, head: usize, width: usize, ellipsis: &str) -> (String, usize) {
+	// Escape sequences may hold slashes, styled text only has its middle cut out
+	if line.contains('\x1b') {
+		return truncate_middle(line, width, ellipsis);
+	}
+
+	let (start, rest) = line.split_at(head);
+	let (path, suffix) = rest.split_at(rest.find(['?', '#']).unwrap_or(rest.len()));
+	let components: Vec<&str> = path.trim_start_matches('/').split('/').collect();
+
+	let mut shortest = line.to_string();
+	for dropped in 1..components.len() {
+		let kept = components[dropped..].join("/");
+		shortest = if ellipsis.is_empty() {
+			format!("{}/{}{}", start, kept, suffix)
+		} else {
+			format!("{}/{}/{}{}", start, ellipsis, kept, suffix)
+		};
+		if ansi::width(&shortest) <= width {
+			let gap = shortfall(&shortest, width);
+			return (format!("{}{}", shortest, " ".repeat(gap)), gap);
+		}
+	}
+
+	// Keep the start whole, and the end of the last component that fits after the ellipsis
+	let lead = format!("{}{}{}", start, if path.is_empty() { "" } else { "/" }, ellipsis);
+	let room = width.saturating_sub(ansi::width(&lead));
+	if !rest.is_empty() && ansi::width(&lead) < width {
+		let kept = fit_end(rest, room);
+		let gap = shortfall(kept, room);
+		return (format!("{}{}{}", lead, " ".repeat(gap), kept), gap);
+	}
+
+	truncate_middle(&shortest, width, ellipsis)
+}
+
+/// Returns the end of the first named component of a path: `/usr` in `/usr/local/bin`.
+fn path_head(line: &str) -> usize {
+	let start = line.len() - line.trim_start_matches('/').len();
+	line[start..].find('/').map_or(line.len(), |end| start + end)
+}
+
+/// Returns the end of the scheme and host of a URL: `https://example.com` in
+/// `https://example.com/docs/index.html`. Lines without a scheme are read as paths.
+fn url_head(line: &str) -> usize {
+	match line.find("://") {
+		Some(scheme) => {
+			let host = scheme + 3;
+			line[host..].find(['/', '?', '#']).map_or(line.len(), |end| host + end)
+		},
+		None => path_head(line),
+	}
+}
+
+/// Stretches the spaces between the words of `line` so that it fills `width` display columns.
+///
+/// The spaces left over when they do not divide evenly go to the first gaps. Lines of a single
+/// word, or already as wide as `width`, are returned as they are.
+fn justify_line(line: &str, width: usize) -> String {
+	let words: Vec<&str> = line.split_whitespace().collect();
+	let text_width: usize = words.iter().map(|word| ansi::width(word)).sum();
+	let gaps = words.len().saturating_sub(1);
+
+	if gaps == 0 || text_width + gaps >= width {
+		return line.to_string();
+	}
+
+	let spaces = width - text_width;
+	let mut justified = String::from(words[0]);
+	for (i, word) in words[1..].iter().enumerate() {
+		let gap = spaces / gaps + usize::from(i < spaces % gaps);
+		justified.push_str(&" ".repeat(gap));
+		justified.push_str(word);
+	}
+	justified
+}
+
+/// Counts the visible grapheme clusters of `text`, the characters a reader sees.
+fn count_graphemes(text: &str) -> usize {
+	ansi::strip(text).graphemes(true).count()
+}
+
+/// A piece of a word being wrapped, measured by its display width.
+#[derive(Debug)]
+struct Piece<'a> {
+	text: &'a str,
+	whitespace: &'a str,
+	penalty: &'a str,
+	width: usize,
+}
+
+impl textwrap::core::Fragment for Piece<'_> {
+	fn width(&self) -> f64 {
+		self.width as f64
+	}
+
+	fn whitespace_width(&self) -> f64 {
+		ansi::width(self.whitespace) as f64
+	}
+
+	fn penalty_width(&self) -> f64 {
+		ansi::width(self.penalty) as f64
+	}
+}
+
+/// Wraps `line` at word boundaries to fit in `width` display columns.
+///
+/// Words are found and split at hyphens as `textwrap` does, but measured by display width, and
+/// words wider than `width` are broken between grapheme clusters rather than characters. The
+/// pieces are then wrapped together, so the rest of a broken word shares its line with what
+/// follows. Styles are not carried over to the following lines, see [`ansi::carry`].
+fn wrap_line(line: &str, width: usize) -> Vec<String> {
+	let width = width.max(1);
+	let options = textwrap::Options::new(width);
+	let words = textwrap::word_splitters::split_words(options.word_separator.find_words(line), &options.word_splitter);
+
+	let mut pieces: Vec<Piece> = Vec::new();
+	for word in words {
+		let mut rest: &str = word.word;
+		while ansi::width(rest) > width {
+			// Always take one cluster, even one wider than the line
+			let mut fit = width;
+			let mut head = fit_start(rest, fit);
+			while ansi::width(head) == 0 {
+				fit += 1;
+				head = fit_start(rest, fit);
+			}
+			pieces.push(Piece { text: head, whitespace: "", penalty: "", width: ansi::width(head) });
+			rest = &rest[head.len()..];
 		}
+		pieces.push(Piece { text: rest, whitespace: word.whitespace, penalty: word.penalty, width: ansi::width(rest) });
+	}
+
+	let widths = [width as f64];
+	let penalties = textwrap::wrap_algorithms::Penalties::new();
+	let wrapped = textwrap::wrap_algorithms::wrap_optimal_fit(&pieces, &widths, &penalties)
+		.unwrap_or_else(|_| textwrap::wrap_algorithms::wrap_first_fit(&pieces, &widths));
+
+	let mut lines: Vec<String> = wrapped.into_iter()
+		.map(|pieces| {
+			let mut wrapped_line = String::new();
+			for (i, piece) in pieces.iter().enumerate() {
+				wrapped_line.push_str(piece.text);
+				// Whitespace between pieces, the penalty at the end of the line
+				wrapped_line.push_str(if i + 1 < pieces.len() { piece.whitespace } else { piece.penalty });
+			}
+			wrapped_line
+		})
+		.collect();
+
+	if lines.is_empty() {
+		lines.push(String::new());
 	}
+
+	lines
 }
 
 #[derive(Args, Clone)]
@@ -303,6 +932,23 @@ pub struct TextFormatter {
 	#[arg(short, long)]
 	pub no_ellipsis: bool,
 
+	/// Marker for truncated text, `{}` stands for the number of hidden characters, e.g. "[+{}]"
+	#[arg(default_value = "...")]
+	#[arg(long)]
+	pub ellipsis: String,
+
+	/// Maximum number of wrapped lines, the last line kept ends with the ellipsis
+	#[arg(long)]
+	pub max_lines: Option<usize>,
+
+	/// Wrap paragraphs as a whole, keeping blank lines, list items, indentation and code blocks
+	#[arg(long)]
+	pub reflow: bool,
+
+	/// Rewrap comment blocks, keeping prefixes such as `// `, `# `, ` * ` or `> ` on every line
+	#[arg(long)]
+	pub comment: bool,
+
 	// Determines whether to pad decimal digits with trailing zeros to maintain
 	//   consistent appearance. If set to true, decimal numbers will display
 	//   the specified number of digits after the decimal point.
@@ -332,6 +978,72 @@ pub struct TextFormatter {
 	#[arg(short, long)]
 	pub thousand_separator: char,
 
+	/// How integer digits are grouped by the thousand separator
+	#[arg(default_value_t = Grouping::THOUSANDS)]
+	#[arg(value_enum)]
+	#[arg(long)]
+	pub grouping: Grouping,
+
+	/// Number locale, overrides the decimal separator, thousand separator and grouping
+	#[arg(value_enum)]
+	#[arg(long, alias = "output-locale")]
+	pub locale: Option<Locale>,
+
+	/// Decimal separator of numbers in the input, defaults to the decimal separator
+	#[arg(long)]
+	pub input_decimal_separator: Option<char>,
+
+	/// Thousand separator of numbers in the input, defaults to the thousand separator
+	#[arg(long)]
+	pub input_thousand_separator: Option<char>,
+
+	/// Number locale of the input, overrides the input separators
+	#[arg(value_enum)]
+	#[arg(long)]
+	pub input_locale: Option<Locale>,
+
+	/// How numbers are displayed
+	#[arg(default_value_t = NumberDisplay::PLAIN)]
+	#[arg(value_enum)]
+	#[arg(long)]
+	pub number_display: NumberDisplay,
+
+	/// Currency symbol added to numbers
+	#[arg(long)]
+	pub currency: Option<String>,
+
+	/// Whether the currency symbol goes before or after the number
+	#[arg(default_value_t = CurrencyPosition::PREFIX)]
+	#[arg(value_enum)]
+	#[arg(long)]
+	pub currency_position: CurrencyPosition,
+
+	/// Show negative numbers in parentheses, as in accounting
+	#[arg(long)]
+	pub accounting: bool,
+
+	/// Show a `+` sign on positive numbers
+	#[arg(long)]
+	pub plus_sign: bool,
+
+	/// How numbers are rounded to the maximum decimal digits
+	#[arg(default_value_t = Rounding::HALF_UP)]
+	#[arg(value_enum)]
+	#[arg(long)]
+	pub rounding: Rounding,
+
+	/// Only read plain decimals as numbers, not `inf`, `NaN` or exponents such as `1e5`
+	#[arg(long)]
+	pub strict: bool,
+
+	/// Round numbers to this many significant figures instead of the maximum decimal digits
+	#[arg(long, value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+	pub sig_figs: Option<usize>,
+
+	/// Exact number of decimal digits shown, set per column by auto precision.
+	#[arg(skip)]
+	pub decimal_digits: Option<usize>,
+
 	// Specifies the alignment of the text within the field.
 	// - `Alignment::AUTO`: Automatically aligns numeric text to the right.
 	// - `Alignment::RIGHT`: Forces right alignment for both numeric and non-numeric text.
@@ -341,6 +1053,12 @@ pub struct TextFormatter {
 	#[arg(short, long)]
 	pub alignment: Alignment,
 
+	/// Alignment of the last line of each paragraph of justified text
+	#[arg(default_value_t = Alignment::LEFT)]
+	#[arg(value_enum)]
+	#[arg(long)]
+	pub last_line: Alignment,
+
 	/// A flag indicating whether the content being formatted is numeric.
 	/// This can influence how certain formatting rules are applied, such as decimal padding.
 	#[arg(hide = true)]
@@ -356,12 +1074,31 @@ impl Default for TextFormatter {
 			width:                     48, // Default 48
 			frame:        Frame::TRUNCATE, // Default TRUNCATE
 			alignment:    Alignment::AUTO, // Default text left, numbers right
+			last_line:    Alignment::LEFT, // Default last justified line left
 			no_ellipsis:            false, // Default add an ellipsis to truncated text
+			ellipsis:    "...".to_string(), // Default three dots
+			max_lines:               None, // Default keep every wrapped line
+			reflow:                 false, // Default wrap every line on its own
+			comment:                false, // Default wrap comment markers as text
 			pad_decimal_digits:     false, // Default dont pad decimal places
 			max_decimal_digits:         2, // Default to 2 decimal places
 			decimal_separator:        '.', // Default decimal separator
 			use_thousand_separator: false, // Default no thousands grouping
 			thousand_separator:       ',', // Default `,`
+			grouping: Grouping::THOUSANDS, // Default groups of three
+			locale:                  None, // Default use the separators as given
+			input_decimal_separator: None, // Default read with the decimal separator
+			input_thousand_separator: None, // Default read with the thousand separator
+			input_locale:            None, // Default read with the output locale
+			number_display: NumberDisplay::PLAIN, // Default plain decimals
+			currency:                None, // Default no currency symbol
+			currency_position: CurrencyPosition::PREFIX, // Default symbol before the number
+			accounting:             false, // Default `-` for negative numbers
+			plus_sign:              false, // Default no sign on positive numbers
+			rounding:   Rounding::HALF_UP, // Default round halves away from zero
+			strict:                 false, // Default also read exponents, inf and NaN
+			sig_figs:                None, // Default round to max_decimal_digits
+			decimal_digits:          None, // Default decimal digits follow the number
 			is_numeric:              None, // Unknown
 		}
 	}
@@ -417,6 +1154,31 @@ impl TextFormatter {
 		self
 	}
 
+	/// Sets the marker of truncated text.
+	///
+	/// # Arguments
+	///
+	/// * `ellipsis` - The marker, `{}` is replaced by the number of hidden characters.
+	pub fn set_ellipsis(&mut self, ellipsis: String) -> &mut Self {
+		self.ellipsis = ellipsis;
+		self
+	}
+
+	pub fn set_max_lines(&mut self, max_lines: Option<usize>) -> &mut Self {
+		self.max_lines = max_lines;
+		self
+	}
+
+	pub fn set_reflow(&mut self, reflow: bool) -> &mut Self {
+		self.reflow = reflow;
+		self
+	}
+
+	pub fn set_comment(&mut self, comment: bool) -> &mut Self {
+		self.comment = comment;
+		self
+	}
+
 	pub fn set_pad_decimal_digits(&mut self, pad_decimal_digits: bool) -> &mut Self {
 		self.pad_decimal_digits = pad_decimal_digits;
 		self
@@ -446,39 +1208,176 @@ impl TextFormatter {
 		self.alignment = alignment;
 		self
 	}
-}
 
-impl TextFormatter {
+	pub fn set_last_line(&mut self, last_line: Alignment) -> &mut Self {
+		self.last_line = last_line;
+		self
+	}
 
-	/// Checks if the content is numeric by first checking the cached value.
-	/// If not cached, normalizes the content and checks if it can be parsed as f64.
+	pub fn set_grouping(&mut self, grouping: Grouping) -> &mut Self {
+		self.grouping = grouping;
+		self
+	}
+
+	/// Applies a number locale preset.
 	///
-	/// # Returns
+	/// Sets the decimal separator, the thousand separator and the grouping from `locale`, both
+	/// for reading numbers and for formatting them.
 	///
-	/// A boolean indicating whether the content is numeric.
-	pub fn is_numeric(&mut self) -> bool {
-		// Return cached result if available
-		if let Some(is_numeric) = self.is_numeric {
+	/// # Arguments
+	///
+	/// * `locale` - The locale preset.
+	pub fn set_locale(&mut self, locale: Locale) -> &mut Self {
+		self.locale = Some(locale);
+		self.decimal_separator = locale.decimal_separator();
+		self.thousand_separator = locale.thousand_separator();
+		self.grouping = locale.grouping();
+		self
+	}
+
+	pub fn set_number_display(&mut self, number_display: NumberDisplay) -> &mut Self {
+		self.number_display = number_display;
+		self
+	}
+
+	pub fn set_currency(&mut self, currency: Option<String>) -> &mut Self {
+		self.currency = currency;
+		self
+	}
+
+	pub fn set_currency_position(&mut self, currency_position: CurrencyPosition) -> &mut Self {
+		self.currency_position = currency_position;
+		self
+	}
+
+	pub fn set_accounting(&mut self, accounting: bool) -> &mut Self {
+		self.accounting = accounting;
+		self
+	}
+
+	pub fn set_plus_sign(&mut self, plus_sign: bool) -> &mut Self {
+		self.plus_sign = plus_sign;
+		self
+	}
+
+	pub fn set_rounding(&mut self, rounding: Rounding) -> &mut Self {
+		self.rounding = rounding;
+		self
+	}
+
+	pub fn set_strict(&mut self, strict: bool) -> &mut Self {
+		self.strict = strict;
+		self
+	}
+
+	pub fn set_sig_figs(&mut self, sig_figs: Option<usize>) -> &mut Self {
+		self.sig_figs = sig_figs;
+		self
+	}
+
+	/// Sets the exact number of decimal digits numbers are padded to.
+	///
+	/// Numbers are still rounded to `max_decimal_digits` or `sig_figs` first, this only
+	/// pads them, so a column can show the same number of decimal digits throughout.
+	pub fn set_decimal_digits(&mut self, decimal_digits: Option<usize>) -> &mut Self {
+		self.decimal_digits = decimal_digits;
+		self
+	}
+
+	pub fn set_input_decimal_separator(&mut self, input_decimal_separator: Option<char>) -> &mut Self {
+		self.input_decimal_separator = input_decimal_separator;
+		self
+	}
+
+	pub fn set_input_thousand_separator(&mut self, input_thousand_separator: Option<char>) -> &mut Self {
+		self.input_thousand_separator = input_thousand_separator;
+		self
+	}
+
+	/// Applies a number locale preset to reading numbers only.
+	///
+	/// Numbers are read with the separators of `locale` and formatted with the decimal
+	/// separator, thousand separator and grouping of the formatter, so `1.234,5` read as
+	/// `de_DE` can be shown as `1,234.5`.
+	///
+	/// # Arguments
+	///
+	/// * `locale` - The locale preset of the input.
+	pub fn set_input_locale(&mut self, locale: Locale) -> &mut Self {
+		self.input_locale = Some(locale);
+		self.input_decimal_separator = Some(locale.decimal_separator());
+		self.input_thousand_separator = Some(locale.thousand_separator());
+		self
+	}
+}
+
+impl TextFormatter {
+
+	/// Checks if the content is numeric by first checking the cached value.
+	/// If not cached, normalizes the content and checks if it can be parsed as a number.
+	///
+	/// # Returns
+	///
+	/// A boolean indicating whether the content is numeric.
+	pub fn is_numeric(&mut self) -> bool {
+		// Return cached result if available
+		if let Some(is_numeric) = self.is_numeric {
 			return is_numeric;
 		}
 
 		// Ensure that text is present (unwrap Option to get &str)
 		let normalized_content = if let Some(text_str) = &self.text {
-			text_str
-				.replace(self.thousand_separator, "")  // Using characters directly
-				.replace(self.decimal_separator, ".")
+			self.normalize_number(text_str)
 		} else {
 			String::new()
 		};
 
 
-		// Try to parse the normalized content as f64 and cache the result
-		self.is_numeric = Some(normalized_content.parse::<f64>().is_ok());
+		// Try to parse the normalized content as a number and cache the result
+		self.is_numeric = Some(self.is_number(&normalized_content));
 		
 		// Return the newly cached result
 		self.is_numeric.unwrap()
 	}
 
+	/// Rewrites a number written with the input separators into Rust's notation.
+	///
+	/// The input separators default to the output ones. Thousand separators are removed and
+	/// the decimal separator becomes `.`. When the thousand separator is a space, any kind of
+	/// space is accepted, so numbers grouped with a no-break space still read when the input
+	/// used a plain one, and the other way around.
+	fn normalize_number(&self, text: &str) -> String {
+		let spaces = [' ', '\u{A0}', '\u{202F}'];
+		let text = text.trim();
+		let thousand_separator = self.input_thousand_separator.unwrap_or(self.thousand_separator);
+		let decimal_separator = self.input_decimal_separator.unwrap_or(self.decimal_separator);
+
+		let ungrouped = if spaces.contains(&thousand_separator) {
+			text.replace(spaces, "")
+		} else {
+			text.replace(thousand_separator, "")
+		};
+
+		ungrouped.replace(decimal_separator, ".")
+	}
+
+	/// Reads `text` as a decimal number with the input separators.
+	///
+	/// # Returns
+	///
+	/// The number, or `None` when `text` is not a plain or (unless `strict`) exponent decimal.
+	pub fn parse_number(&self, text: &str) -> Option<Decimal> {
+		Decimal::parse(&self.normalize_number(text), self.strict)
+	}
+
+	/// Checks whether normalized text is a number.
+	///
+	/// Decimals are always numbers. Unless `strict`, exponent forms such as `1e5` and the
+	/// special values `inf` and `NaN` are numbers as well.
+	fn is_number(&self, normalized: &str) -> bool {
+		Decimal::parse(normalized, self.strict).is_some() || (!self.strict && normalized.parse::<f64>().is_ok())
+	}
+
 	pub fn is_hex(&self) -> bool {
 		// Check if the text exists
 		if let Some(ref text) = self.text {
@@ -548,7 +1447,7 @@ impl TextFormatter {
 
         // Calculate the maximum width of the lines
         let max_line_width = lines.iter()
-            .map(|line| line.width())
+            .map(|line| ansi::width(line))
             .max()
             .unwrap_or(0); // Fallback to 0 if there are no lines
 
@@ -566,10 +1465,10 @@ impl TextFormatter {
         let aligned_lines: Vec<String> = lines
             .iter()
             .map(|line| {
-                let line_width = line.width(); // Get the width of the current line
+                let line_width = ansi::width(line); // Get the visible width of the current line
                 if line_width < effective_width {
                     // Right-align with padding if line is shorter than the effective width
-                    format!("{:>width$}", line, width = effective_width)
+                    format!("{}{}", " ".repeat(effective_width - line_width), line)
                 } else {
                     // No alignment needed if the line is already wider than or equal to the effective width
                     line.to_string()
@@ -655,19 +1554,19 @@ impl TextFormatter {
 		let mut centered_lines: Vec<String> = Vec::with_capacity(lines.len());
 
 		for line in lines {
-			let text_width = line.width();
+			let text_width = ansi::width(&line);
 
 			// Determine the appropriate width for truncation or wrapping
 			let max_width = if text_width > effective_width {
 				match self.frame {
 					Frame::WRAP => {
-						// Use textwrap to wrap the line
-						textwrap::wrap(&line, effective_width).join("\n")
+						// Wrap the line at words, keeping grapheme clusters whole
+						ansi::carry(&wrap_line(&line, effective_width)).join("\n")
 					},
 					Frame::NONE => line.clone(), // No modification
 					_ => {
 						// Truncate the line if it exceeds the effective width
-						line.chars().take(effective_width).collect()
+						ansi::close(fit_start(&line, effective_width))
 					},
 				}
 			} else {
@@ -675,7 +1574,7 @@ impl TextFormatter {
 			};
 
 			// Calculate padding for centering
-			let total_padding = effective_width.saturating_sub(max_width.width());
+			let total_padding = effective_width.saturating_sub(ansi::width(&max_width));
 			let left_padding = total_padding / 2;
 
 			// Center the line with padding
@@ -693,11 +1592,125 @@ impl TextFormatter {
 		result
 	}
 
+	/// Returns the ellipsis for `hidden` characters left out, or nothing when the ellipsis is
+	/// disabled or would take the whole width.
+	fn marker(&self, hidden: usize) -> String {
+		let marker = self.ellipsis.replace("{}", &hidden.to_string());
+		if self.no_ellipsis || ansi::width(&marker) >= self.width {
+			String::new()
+		} else {
+			marker
+		}
+	}
+
+	/// Shortens `line` with `cut`, which is given the ellipsis to put in place of the text it
+	/// leaves out, besides the `dropped` characters already left out. `cut` returns the line
+	/// with the number of spaces it added as padding.
+	///
+	/// The number of hidden characters changes the width of an ellipsis such as `[+{}]`, and
+	/// with it the text that fits, so the line is cut again until the count holds.
+	fn fit_marked(&self, line: &str, dropped: usize, cut: impl Fn(&str) -> (String, usize)) -> String {
+		let total = dropped + count_graphemes(line);
+		let mut hidden = dropped;
+		let mut marked = String::new();
+
+		for _ in 0..4 {
+			let marker = self.marker(hidden);
+			let padding;
+			(marked, padding) = cut(&marker);
+			let shown = count_graphemes(&marked).saturating_sub(count_graphemes(&marker) + padding);
+			let counted = total.saturating_sub(shown);
+			if counted == hidden {
+				break;
+			}
+			hidden = counted;
+		}
+
+		marked
+	}
+
+	/// Justifies the wrapped `lines` of a paragraph to `width`, aligning the last line with
+	/// `self.last_line`.
+	fn justify(&self, mut lines: Vec<String>, width: usize) -> Vec<String> {
+		let Some(last) = lines.pop() else {
+			return lines;
+		};
+
+		let gap = width.saturating_sub(ansi::width(&last));
+		let last = match self.last_line {
+			Alignment::JUSTIFY => justify_line(&last, width),
+			Alignment::RIGHT   => format!("{}{}", " ".repeat(gap), last),
+			Alignment::CENTER  => format!("{}{}", " ".repeat(gap / 2), last),
+			_ => last,
+		};
+
+		lines.iter()
+			.map(|line| justify_line(line, width))
+			.chain(std::iter::once(last))
+			.collect()
+	}
+
+	/// Wraps a paragraph in the `width` left after its prefixes, justifying it if asked:
+	/// `first` starts the first line and `rest` the following ones.
+	fn wrap_paragraph(&self, text: &str, first: &str, rest: &str, width: usize) -> Vec<String> {
+		let width = width.saturating_sub(ansi::width(first).max(ansi::width(rest))).max(1);
+
+		// Wrap without splitting graphemes, then justify if asked
+		let mut lines = wrap_line(text, width);
+		if self.alignment == Alignment::JUSTIFY {
+			lines = self.justify(lines, width);
+		}
+
+		lines.into_iter()
+			.enumerate()
+			.map(|(i, line)| format!("{}{}", if i == 0 { first } else { rest }, line))
+			.collect()
+	}
+
+	/// Wraps each paragraph of `text` as a whole to `width`, keeping its indentation and list
+	/// marker.
+	fn reflow_lines(&self, text: &str, width: usize) -> Vec<String> {
+		paragraph::blocks(text).into_iter()
+			.flat_map(|block| match block {
+				paragraph::Block::Verbatim(line) => vec![line.to_string()],
+				paragraph::Block::Paragraph { ref text, .. } => {
+					let (first, rest) = block.prefixes();
+					self.wrap_paragraph(text, &first, &rest, width)
+				},
+			})
+			.collect()
+	}
+
+	/// Wraps `text` to `width`, each paragraph as a whole with `reflow`, else each line on its
+	/// own.
+	fn wrap_lines(&self, text: &str, width: usize) -> Vec<String> {
+		if self.reflow {
+			self.reflow_lines(text, width)
+		} else {
+			// Trim whitespace from each line, wrap, and join the results
+			text.lines()
+				.map(str::trim)                                           // Trim leading and trailing whitespace
+				.flat_map(|line| self.wrap_paragraph(line, "", "", width)) // Wrap each line on its own
+				.collect()                                                 // Collect wrapped lines
+		}
+	}
+
 	/// Wraps the text to fit within the specified width.
 	///
 	/// This function takes the text stored in `self.text`, trims leading and trailing
 	/// whitespace from each line, and wraps the text to fit within the specified width.
 	/// The wrapped lines are then joined into a single string with newline characters.
+	/// When there are more lines than `max_lines`, the rest are dropped and the last line
+	/// kept ends with the ellipsis. With `Alignment::JUSTIFY` the lines of every input line
+	/// but the last are stretched to the width, and the last is aligned with `last_line`.
+	///
+	/// With `reflow`, the lines of each paragraph are joined and wrapped together instead,
+	/// see [`paragraph::blocks`]. Paragraphs keep their indentation and list items a hanging
+	/// indent, while blank lines and fenced code blocks are kept as they are.
+	///
+	/// With `comment`, runs of lines starting with the same comment or quote marker, such as
+	/// `// ` or `> > `, are reflowed in the width left after it and every line gets it again,
+	/// see [`paragraph::comments`]. Lines without a marker are wrapped as usual.
 	///
 	/// If no text is provided, an error message is printed, and an empty string is returned.
 	///
@@ -707,7 +1720,7 @@ impl TextFormatter {
 	///
 	/// # Example
 	///
-	/// ```
+	/// ```ignore
 	/// let mut formatter = TextFormatter::new(Some("This is a long line of text that will be wrapped to fit within a specified width."));
 	/// formatter.set_width(20);
 	/// let wrapped_text = formatter.wrap();
@@ -719,18 +1732,45 @@ impl TextFormatter {
 			""
 		});
 
-		// Trim whitespace from each line, wrap, and join the results
-		let wrapped_text = textwrap::wrap(
-			&text.lines()
-				.map(str::trim)          // Trim leading and trailing whitespace
-				.collect::<Vec<_>>()     // Collect trimmed lines
-				.join("\n"),             // Join trimmed lines into a single string
-			self.width,
-		)
-		.into_iter()
-		.map(String::from)               // Convert Cow to String
-		.collect::<Vec<_>>()             // Collect wrapped lines
-		.join("\n");                     // Join wrapped lines with newlines
+		let mut wrapped_lines: Vec<String> = if self.comment {
+			// Reflow the lines of each comment prefix in the width left, then prefix them again
+			paragraph::comments(text).into_iter()
+				.flat_map(|(prefix, body)| {
+					let width = self.width.saturating_sub(ansi::width(prefix));
+					let mut lines = match prefix.is_empty() {
+						true => self.wrap_lines(&body, width),
+						false => self.reflow_lines(&body, width),
+					};
+					if lines.is_empty() {
+						lines.push(String::new()); // A bare prefix
+					}
+					lines.into_iter()
+						.map(move |line| match line.is_empty() {
+							true => prefix.trim_end().to_string(), // No trailing space on blank lines
+							false => format!("{}{}", prefix, line),
+						})
+				})
+				.collect()
+		} else {
+			self.wrap_lines(text, self.width)
+		};
+
+		// Drop the lines past the maximum, marking the last line kept
+		if let Some(max_lines) = self.max_lines.map(|max| max.max(1)) {
+			if wrapped_lines.len() > max_lines {
+				let dropped: usize = wrapped_lines[max_lines..].iter().map(|line| count_graphemes(line)).sum();
+				wrapped_lines.truncate(max_lines);
+				let last = wrapped_lines.pop().unwrap_or_default();
+				let marked = self.fit_marked(&last, dropped, |marker| {
+					let kept = fit_start(&last, self.width.saturating_sub(ansi::width(marker)));
+					(ansi::close(&format!("{}{}", kept.trim_end(), marker)), 0)
+				});
+				wrapped_lines.push(marked);
+			}
+		}
+
+		// Close styles at the end of each line and reopen them on the next
+		let wrapped_text = ansi::carry(&wrapped_lines).join("\n");
 
 		// Update self.text with the new wrapped text
 		self.text = Some(wrapped_text.clone());
@@ -745,20 +1785,31 @@ impl TextFormatter {
 	/// frame type. If the text exceeds the maximum width:
 	/// - For `Frame::CHOP`, characters are removed from the beginning of the line,
 	///   and ellipses are prepended if applicable.
+	/// - For `Frame::MIDDLE`, characters are removed from the middle of the line.
+	/// - For `Frame::PATH` and `Frame::URL`, whole path components are removed from the middle
+	///   of the line first, keeping the start of the path or the scheme and host of the URL,
+	///   and the last component.
 	/// - By default, characters are collected from the start until the width is exceeded,
 	///   with ellipses appended at the end if applicable.
 	///
+	/// The ellipsis is `self.ellipsis` measured by its display width, with `{}` replaced by the
+	/// number of characters left out. It is left out when it would take the whole width.
+	///
+	/// A truncated line takes exactly `self.width` columns. Text next to the cut is never
+	/// trimmed, and a wide character that would straddle the cut is left out and its columns
+	/// filled with spaces beside the ellipsis.
+	///
 	/// # Returns
 	/// A `String` containing the truncated text. If the original text fits within the width,
 	/// the original text is returned unchanged.
 	///
 	/// # Examples
-	/// ```
+	/// ```ignore
 	/// let mut text_instance = Text::new("This is a long line of text that might need truncation.");
 	/// text_instance.set_width(20);
 	/// text_instance.set_frame(Frame::CHOP);
 	/// let truncated = text_instance.truncate();
-	/// assert_eq!(truncated, "...of text that might need truncation.");
+	/// assert_eq!(truncated, "...ht need truncation.");
 	/// ```
 	pub fn truncate(&mut self) -> String {
 		// Ensure text is left-aligned or right-aligned based on frame
@@ -777,51 +1828,34 @@ impl TextFormatter {
 		let truncated_lines: Vec<String> = text
 			.lines()
 			.map(|line| {
-				let text_width = line.width();
+				let text_width = ansi::width(line);
 				if text_width > self.width {
-					let ellipsis_len = if self.no_ellipsis { 0 } else { 3 };
-					let max_width = self.width.saturating_sub(ellipsis_len);
-
-					let mut current_width = 0;
-					let mut truncated = String::new();
-
-					match self.frame {
-						Frame::CHOP => {
-							// Iterate from the end to find how much to keep
-							for c in line.chars().rev() {
-								let char_width = c.width().unwrap_or(0);
-								current_width += char_width;
-
-								if current_width > max_width {
-									truncated.insert(0, c); // Prepend character
-								} else {
-									truncated.insert(0, c);
-								}
-							}
-							// Prepend ellipses if applicable
-							if !self.no_ellipsis && self.width > 3 {
-								format!("...{}", truncated.trim())
-							} else {
-								truncated.trim().to_string()
-							}
-						},
-						_ => {
-							for c in line.chars() {
-								let char_width = c.width().unwrap_or(0);
-								if current_width + char_width > max_width {
-									break;
-								}
-								current_width += char_width;
-								truncated.push(c);
-							}
-							// Append ellipses if applicable
-							if !self.no_ellipsis && self.width > 3 {
-								format!("{}...", truncated.trim())
-							} else {
-								truncated.trim().to_string()
-							}
-						},
-					}
+					self.fit_marked(line, 0, |ellipsis| {
+						let max_width = self.width.saturating_sub(ansi::width(ellipsis));
+
+						match self.frame {
+							Frame::MIDDLE => truncate_middle(line, self.width, ellipsis),
+							Frame::PATH => elide_components(line, path_head(line), self.width, ellipsis),
+							Frame::URL => elide_components(line, url_head(line), self.width, ellipsis),
+							Frame::CHOP => {
+								// Keep the end that fits, reopening the styles open before it
+								let kept = fit_end(line, max_width);
+								let reopen = ansi::style_at_end(&line[..line.len() - kept.len()]).open();
+								let gap = shortfall(kept, max_width);
+
+								// Prepend the ellipsis, empty when disabled
+								(ansi::close(&format!("{}{}{}{}", ellipsis, " ".repeat(gap), reopen, kept)), gap)
+							},
+							_ => {
+								// Keep the start that fits
+								let kept = fit_start(line, max_width);
+								let gap = shortfall(kept, max_width);
+
+								// Append the ellipsis, empty when disabled
+								(format!("{}{}{}", ansi::close(kept), " ".repeat(gap), ellipsis), gap)
+							},
+						}
+					})
 				} else {
 					line.to_string() // Return original line if no truncation is needed
 				}
@@ -843,94 +1877,676 @@ impl TextFormatter {
 		let text = self.text.as_deref().unwrap_or_else(|| {
 			eprintln!("Error: No text provided.");
 			self.is_numeric = None; // Set to None since no text is available
-			return "";
+			""
 		});
 
 		// Normalize text by replacing custom separators
-		let normalized = text
-			.replace(self.thousand_separator, "")  // Remove thousand separators
-			.replace(self.decimal_separator, ".");   // Normalize decimal separator
-
-		// Attempt to parse the normalized text as a number
-		match normalized.parse::<f64>() {
-			Ok(number) => {
-				// Format the number with native Rust formatting
-				let formatted = if self.pad_decimal_digits {
-					format!("{:.*}", self.max_decimal_digits, number)
-				} else {
-					format!("{}", number)
-				};
-
-				// Split formatted number into integer and fractional parts
-				let parts: Vec<&str> = formatted.split('.').collect();
-				let integer_part = parts[0];
-				let fractional_part = if parts.len() > 1 { parts[1] } else { "" };
-
-				// Apply thousands separators if needed
-				let integer_with_thousands = if self.use_thousand_separator {
-					let integer_chars: Vec<char> = integer_part.chars().rev().collect();
-					let mut result_chars = Vec::new();
-					for (i, ch) in integer_chars.iter().enumerate() {
-						if i > 0 && i % 3 == 0 {
-							result_chars.push(self.thousand_separator);
-						}
-						result_chars.push(*ch);
-					}
-					result_chars.reverse();
-					result_chars.iter().collect::<String>()
-				} else {
-					integer_part.to_string()
-				};
+		let normalized = self.normalize_number(text);
+
+		// Attempt to parse the normalized text as an exact decimal
+		match Decimal::parse(&normalized, self.strict) {
+			Some(number) => {
+				// Format the number, then lay it out as numeric text
+				self.text = Some(self.format_number(&number));
+				self.layout(true)
+			}
+			// inf and NaN have no digits to format, they are only aligned as numbers
+			None if self.is_number(&normalized) => self.layout(true),
+			None => self.layout(false),
+		}
+	}
 
-				// Combine integer and fractional parts and replace decimal separator
-				let formatted_result = if !fractional_part.is_empty() {
-					format!("{}.{}", integer_with_thousands, fractional_part)
-				} else {
-					integer_with_thousands
-				};
-
-				// Replace decimal separator with custom one
-				let final_formatted_number = formatted_result.replace('.', &self.decimal_separator.to_string());
-
-				// Apply alignment
-				let aligned_result = match self.alignment {
-					Alignment::NONE   => final_formatted_number,
-					Alignment::LEFT   => left(Some(&final_formatted_number)),
-					Alignment::AUTO   => right(Some(&final_formatted_number), Some(self.width)),
-					Alignment::RIGHT  => right(Some(&final_formatted_number), Some(self.width)),
-					Alignment::CENTER => center(Some(&final_formatted_number), Some(self.width)),
-				};
-
-				// Set properties
-				self.is_numeric = Some(true);
-				self.text = Some(aligned_result.clone()); // Store the formatted text
-				aligned_result // Return the formatted text
+	/// Lays out the text within `width` without reading it as a number.
+	///
+	/// Numeric text is only aligned, so numbers formatted earlier by `text()` keep their exact
+	/// form. Other text is first truncated or wrapped according to `frame`, then aligned.
+	///
+	/// # Arguments
+	///
+	/// * `is_numeric` - Whether the text is a formatted number.
+	///
+	/// # Returns
+	///
+	/// A `String` containing the laid out text.
+	pub fn layout(&mut self, is_numeric: bool) -> String {
+		if is_numeric {
+			let final_formatted_number = self.text.clone().unwrap_or_default();
+
+			// Apply alignment
+			let aligned_result = match self.alignment {
+				Alignment::NONE    => final_formatted_number,
+				Alignment::LEFT    => left(Some(&final_formatted_number)),
+				Alignment::AUTO    => right(Some(&final_formatted_number), Some(self.width)),
+				Alignment::RIGHT   => right(Some(&final_formatted_number), Some(self.width)),
+				Alignment::JUSTIFY => right(Some(&final_formatted_number), Some(self.width)),
+				Alignment::CENTER  => center(Some(&final_formatted_number), Some(self.width)),
+			};
+
+			// Set properties
+			self.is_numeric = Some(true);
+			self.text = Some(aligned_result.clone()); // Store the formatted text
+			aligned_result // Return the formatted text
+		} else {
+			// Format as general text based on the frame setting
+			let formatted_text = match self.frame {
+				Frame::TRUNCATE | Frame::CHOP | Frame::MIDDLE | Frame::PATH | Frame::URL => self.truncate(),
+				Frame::WRAP => self.wrap(),
+				Frame::NONE => {
+					let text_value = self.text.clone().expect("Text is None");
+					self.is_numeric = Some(false); // Set to false because it couldn't parse
+					text_value // Return original text
+				},
+			};
+
+			// Close styles left open, so they never leak into the next cell or line
+			let formatted_text = ansi::carry(&formatted_text.lines().collect::<Vec<_>>()).join("\n");
+
+			// Apply alignment based on the settings
+			let aligned_result = match self.alignment {
+				Alignment::AUTO | Alignment::NONE | Alignment::JUSTIFY => formatted_text.clone(),
+				Alignment::LEFT => left(Some(&formatted_text)),
+				Alignment::RIGHT => right(Some(&formatted_text), Some(self.width)),
+				Alignment::CENTER => center(Some(&formatted_text), Some(self.width)),
+			};
+
+			// Set properties
+			self.is_numeric = Some(false);
+			self.text = Some(aligned_result.clone()); // Store the formatted text
+			aligned_result // Return the formatted text
+		}
+	}
+
+	/// Formats `number` in the selected display mode, then adds the currency symbol and sign.
+	///
+	/// The display modes format the magnitude of the number, so the sign styles apply the
+	/// same way to all of them: `-` by default, parentheses with `accounting`, and `+` on
+	/// positive numbers with `plus_sign`.
+	fn format_number(&self, number: &Decimal) -> String {
+		let magnitude = number.abs();
+
+		let digits = match self.number_display {
+			NumberDisplay::PLAIN       => self.format_decimal(&magnitude),
+			NumberDisplay::BYTES       => self.format_bytes(magnitude.to_f64()),
+			NumberDisplay::SI          => self.format_si(magnitude.to_f64()),
+			NumberDisplay::DURATION    => self.format_duration(magnitude.to_f64()),
+			NumberDisplay::PERCENT     => format!("{}%", self.format_decimal(&self.round_decimal(&magnitude.shift(2)))),
+			NumberDisplay::SCIENTIFIC  => self.format_scientific(&magnitude, false),
+			NumberDisplay::ENGINEERING => self.format_scientific(&magnitude, true),
+		};
+
+		let with_currency = match (&self.currency, self.currency_position) {
+			(Some(symbol), CurrencyPosition::PREFIX) => format!("{}{}", symbol, digits),
+			(Some(symbol), CurrencyPosition::SUFFIX) => format!("{} {}", digits, symbol),
+			(None, _) => digits,
+		};
+
+		// The sign goes with the number shown, so values that round to zero have none
+		let shown = match self.number_display {
+			NumberDisplay::PLAIN   => self.rounded(&magnitude),
+			NumberDisplay::PERCENT => self.round_decimal(&magnitude.shift(2)),
+			_ => magnitude,
+		};
+
+		if number.is_negative() && !shown.is_zero() {
+			if self.accounting {
+				format!("({})", with_currency)
+			} else {
+				format!("-{}", with_currency)
 			}
-			Err(_) => {
-				// If parsing fails, format as general text based on the frame setting
-				let formatted_text = match self.frame {
-					Frame::TRUNCATE | Frame::CHOP => self.truncate(),
-					Frame::WRAP => self.wrap(),
-					Frame::NONE => {
-						let text_value = self.text.clone().expect("Text is None");
-						self.is_numeric = Some(false); // Set to false because it couldn't parse
-						text_value // Return original text
-					},
-				};
-
-				// Apply alignment based on the settings
-				let aligned_result = match self.alignment {
-					Alignment::AUTO | Alignment::NONE => formatted_text.clone(),
-					Alignment::LEFT => left(Some(&formatted_text)),
-					Alignment::RIGHT => right(Some(&formatted_text), Some(self.width)),
-					Alignment::CENTER => center(Some(&formatted_text), Some(self.width)),
-				};
-
-				// Set properties
-				self.is_numeric = Some(false);
-				self.text = Some(aligned_result.clone()); // Store the formatted text
-				aligned_result // Return the formatted text
+		} else if !number.is_negative() && !shown.is_zero() && self.plus_sign {
+			format!("+{}", with_currency)
+		} else {
+			with_currency
+		}
+	}
+
+	/// Formats `number` as a decimal with the configured separators and precision.
+	///
+	/// The digits are printed exactly as read. With `pad_decimal_digits` the number is rounded
+	/// to `max_decimal_digits` and padded with zeros to that many digits.
+	fn format_decimal(&self, number: &Decimal) -> String {
+		let sign = if number.is_negative() { "-" } else { "" };
+		let rounded = self.rounded(number);
+
+		// Pad to the column's decimal digits, or to the precision with pad_decimal_digits
+		let width = match (self.decimal_digits, self.sig_figs) {
+			(Some(digits), _) => digits,
+			(None, Some(figures)) if self.pad_decimal_digits => (figures as i64 - 1 - rounded.exponent()).max(0) as usize,
+			(None, None) if self.pad_decimal_digits => self.max_decimal_digits,
+			_ => 0,
+		};
+
+		let integer = format!("{}{}", sign, rounded.integer());
+		let fractional_part = format!("{:0<width$}", rounded.fraction(), width = width);
+		let integer_part = integer.as_str();
+
+		// Apply thousands separators if needed
+		let integer_with_thousands = if self.use_thousand_separator {
+			group_digits(integer_part, self.thousand_separator, self.grouping)
+		} else {
+			integer_part.to_string()
+		};
+
+		// Combine integer and fractional parts with the custom decimal separator
+		if !fractional_part.is_empty() {
+			format!("{}{}{}", integer_with_thousands, self.decimal_separator, fractional_part)
+		} else {
+			integer_with_thousands
+		}
+	}
+
+	/// Rounds `number` to `sig_figs` significant figures, or else to `max_decimal_digits`
+	/// fractional digits, with the rounding mode.
+	fn round_decimal(&self, number: &Decimal) -> Decimal {
+		match self.sig_figs {
+			Some(figures) => number.round_significant(figures, self.rounding),
+			None => number.round(self.max_decimal_digits, self.rounding),
+		}
+	}
+
+	/// Rounds `number` the way plain decimals are shown.
+	///
+	/// Numbers are printed exactly as read, unless a precision applies: padded decimal
+	/// digits, significant figures or a column's decimal digits.
+	fn rounded(&self, number: &Decimal) -> Decimal {
+		if self.pad_decimal_digits || self.sig_figs.is_some() || self.decimal_digits.is_some() {
+			self.round_decimal(number)
+		} else {
+			number.clone()
+		}
+	}
+
+	/// Returns the number of decimal digits the text shows once formatted as a number.
+	///
+	/// Auto precision pads a column to the largest of these, counted once the number is
+	/// rounded to its precision. Only plain and percent numbers have decimal digits to line up.
+	///
+	/// # Returns
+	///
+	/// The number of decimal digits, or `None` when the text is not a number or is shown
+	/// in another display mode.
+	pub fn decimal_digits_needed(&self) -> Option<usize> {
+		let normalized = self.normalize_number(self.text.as_deref().unwrap_or(""));
+		let number = Decimal::parse(&normalized, self.strict)?.abs();
+
+		match self.number_display {
+			NumberDisplay::PLAIN   => Some(self.round_decimal(&number).fraction().len()),
+			NumberDisplay::PERCENT => Some(self.round_decimal(&number.shift(2)).fraction().len()),
+			_ => None,
+		}
+	}
+
+	/// Rounds `number` to `max_decimal_digits` fractional digits with the rounding mode.
+	fn round(&self, number: f64) -> f64 {
+		self.round_decimal(&Decimal::from_f64(number)).to_f64()
+	}
+
+	/// Scales `number` down by `base` until it fits below it, rounding to `max_decimal_digits`.
+	///
+	/// # Returns
+	///
+	/// The scaled value and the index of the unit it is expressed in.
+	fn scale(&self, number: f64, base: f64, units: usize) -> (f64, usize) {
+		let mut scaled = number;
+		let mut unit = 0;
+
+		loop {
+			let rounded = self.round(scaled);
+			if rounded.abs() < base || unit + 1 >= units {
+				return (rounded, unit);
+			}
+			scaled /= base;
+			unit += 1;
+		}
+	}
+
+	/// Formats a byte count with binary units: `1.5 KiB`.
+	fn format_bytes(&self, number: f64) -> String {
+		const UNITS: [&str; 7] = ["B", "KiB", "MiB", "GiB", "TiB", "PiB", "EiB"];
+
+		let (scaled, unit) = self.scale(number, 1024.0, UNITS.len());
+		format!("{} {}", self.format_decimal(&Decimal::from_f64(scaled)), UNITS[unit])
+	}
+
+	/// Formats a number with SI prefixes: `1.5k`, `12M`, `250m`.
+	fn format_si(&self, number: f64) -> String {
+		const LARGE: [&str; 7] = ["", "k", "M", "G", "T", "P", "E"];
+		const SMALL: [&str; 5] = ["", "m", "µ", "n", "p"];
+
+		if number != 0.0 && number.abs() < 1.0 {
+			// Scale small numbers up until they reach 1
+			let mut scaled = number;
+			let mut unit = 0;
+			while scaled.abs() < 1.0 && unit + 1 < SMALL.len() {
+				scaled *= 1000.0;
+				unit += 1;
+			}
+			let (scaled, _) = self.scale(scaled, 1000.0, 1);
+			return format!("{}{}", self.format_decimal(&Decimal::from_f64(scaled)), SMALL[unit]);
+		}
+
+		let (scaled, unit) = self.scale(number, 1000.0, LARGE.len());
+		format!("{}{}", self.format_decimal(&Decimal::from_f64(scaled)), LARGE[unit])
+	}
+
+	/// Formats a non-negative number of seconds as a duration, using its two largest units:
+	/// `3h 12m`.
+	///
+	/// Durations under a second are shown in milliseconds.
+	fn format_duration(&self, seconds: f64) -> String {
+		const UNITS: [(&str, u64); 4] = [("d", 86_400), ("h", 3_600), ("m", 60), ("s", 1)];
+
+		if seconds > 0.0 && seconds < 1.0 {
+			return format!("{}ms", (seconds * 1000.0).round());
+		}
+
+		let mut remaining = seconds.round() as u64;
+		let mut parts: Vec<String> = Vec::new();
+
+		for (name, size) in UNITS {
+			let count = remaining / size;
+			remaining %= size;
+
+			if count > 0 || (parts.is_empty() && size == 1) {
+				parts.push(format!("{}{}", count, name));
+			} else if !parts.is_empty() {
+				break; // Skip units below a zero unit: `1h` rather than `1h 0m 5s`
+			}
+
+			if parts.len() == 2 {
+				break;
+			}
+		}
+
+		parts.join(" ")
+	}
+
+	/// Formats a non-negative number in scientific notation, `1.23e4`, or with `engineering`
+	/// in engineering notation, where the exponent is a multiple of three: `12.3e3`.
+	fn format_scientific(&self, number: &Decimal, engineering: bool) -> String {
+		let step = if engineering { 3 } else { 1 };
+		let limit = if engineering { 3 } else { 1 };
+
+		let mut exponent = number.exponent().div_euclid(step) * step;
+		let mut mantissa = self.round_decimal(&number.shift(-exponent));
+
+		// Rounding may carry the mantissa over to another integer digit
+		if mantissa.integer().len() > limit {
+			exponent += step;
+			mantissa = self.round_decimal(&number.shift(-exponent));
+		}
+
+		format!("{}e{}", self.format_decimal(&mantissa), exponent)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn cut(text: &str, width: usize, frame: Frame) -> String {
+		truncate(Some(text), Some(width), None, None, Some(frame))
+	}
+
+	fn cut_with(text: &str, width: usize, ellipsis: &str, frame: Frame) -> String {
+		truncate(Some(text), Some(width), None, Some(ellipsis), Some(frame))
+	}
+
+	#[test]
+	fn fitting_text_is_unchanged() {
+		assert_eq!(cut("short", 10, Frame::TRUNCATE), "short");
+		assert_eq!(cut("exactly10!", 10, Frame::TRUNCATE), "exactly10!");
+		assert_eq!(cut("中文", 4, Frame::TRUNCATE), "中文");
+	}
+
+	#[test]
+	fn truncate_keeps_the_start() {
+		assert_eq!(cut("The quick brown fox", 12, Frame::TRUNCATE), "The quick...");
+	}
+
+	#[test]
+	fn truncate_keeps_spaces_at_the_cut() {
+		assert_eq!(cut("The quick brown fox", 13, Frame::TRUNCATE), "The quick ...");
+	}
+
+	#[test]
+	fn chop_keeps_the_end() {
+		assert_eq!(cut("The quick brown fox", 10, Frame::CHOP), "...own fox");
+		assert_eq!(cut("The quick brown fox", 9, Frame::CHOP), "...wn fox");
+	}
+
+	#[test]
+	fn chop_without_ellipsis() {
+		assert_eq!(truncate(Some("The quick brown fox"), Some(5), Some(true), None, Some(Frame::CHOP)), "n fox");
+	}
+
+	#[test]
+	fn truncate_without_ellipsis() {
+		assert_eq!(truncate(Some("The quick brown fox"), Some(5), Some(true), None, None), "The q");
+	}
+
+	#[test]
+	fn ellipsis_is_left_out_when_it_takes_the_whole_width() {
+		assert_eq!(cut("abcdef", 3, Frame::TRUNCATE), "abc");
+		assert_eq!(cut("abcdef", 2, Frame::CHOP), "ef");
+		assert_eq!(cut("abcdef", 4, Frame::TRUNCATE), "a...");
+	}
+
+	#[test]
+	fn wide_characters_are_padded_at_the_cut() {
+		assert_eq!(cut("中文中文中文", 6, Frame::TRUNCATE), "中 ...");
+		assert_eq!(cut("中文中文中文", 6, Frame::CHOP), "... 文");
+		assert_eq!(cut("中文中文中文", 7, Frame::TRUNCATE), "中文...");
+		assert_eq!(truncate(Some("中文中文"), Some(3), Some(true), None, None), "中 ");
+		assert_eq!(truncate(Some("中文中文"), Some(1), Some(true), None, Some(Frame::CHOP)), " ");
+	}
+
+	#[test]
+	fn truncated_lines_take_exactly_the_width() {
+		let lines = ["中文中文中文中文 and more", "a中b文c中d文e中f文", "👨‍👩‍👧‍👦 family 🇯🇵 flag", "e\u{301}e\u{301}e\u{301}e\u{301}e\u{301}e\u{301}"];
+		for line in lines {
+			for frame in [Frame::TRUNCATE, Frame::CHOP, Frame::MIDDLE, Frame::PATH] {
+				for width in 1..ansi::width(line) {
+					for ellipsis in ["...", "…", ">", "[+{}]"] {
+						let truncated = cut_with(line, width, ellipsis, frame);
+						assert_eq!(ansi::width(&truncated), width, "{:?} {} {} {:?}", line, frame, width, ellipsis);
+					}
+				}
 			}
 		}
 	}
+
+	#[test]
+	fn emoji_sequences_are_kept_whole() {
+		let family = "👨‍👩‍👧‍👦";
+		let line = format!("{}{}{}{}", family, family, family, family);
+		assert_eq!(cut(&line, 7, Frame::TRUNCATE), format!("{}{}...", family, family));
+		assert_eq!(cut(&line, 6, Frame::TRUNCATE), format!("{} ...", family));
+		assert_eq!(cut(&line, 6, Frame::CHOP), format!("... {}", family));
+		assert_eq!(cut("🇯🇵🇫🇷🇩🇪", 5, Frame::TRUNCATE), "🇯🇵...");
+	}
+
+	#[test]
+	fn combining_accents_stay_with_their_letter() {
+		assert_eq!(cut("e\u{301}e\u{301}e\u{301}e\u{301}e\u{301}", 4, Frame::TRUNCATE), "e\u{301}...");
+		assert_eq!(cut("e\u{301}e\u{301}e\u{301}e\u{301}e\u{301}", 4, Frame::CHOP), "...e\u{301}");
+	}
+
+	#[test]
+	fn custom_ellipsis_uses_its_display_width() {
+		assert_eq!(cut_with("The quick brown fox", 10, "…", Frame::TRUNCATE), "The quick…");
+		assert_eq!(cut_with("The quick brown fox", 10, "…", Frame::CHOP), "…brown fox");
+		assert_eq!(cut_with("The quick brown fox", 10, "→→", Frame::TRUNCATE), "The quic→→");
+		assert_eq!(cut_with("中文中文中文", 6, "…", Frame::TRUNCATE), "中文 …");
+	}
+
+	#[test]
+	fn ellipsis_counts_hidden_characters() {
+		assert_eq!(cut_with("The quick brown fox jumps", 12, "[+{}]", Frame::TRUNCATE), "The qui[+18]");
+		assert_eq!(cut_with("The quick brown fox jumps", 12, "[+{}]", Frame::CHOP), "[+18]x jumps");
+		assert_eq!(cut_with("中文中文中文中文", 9, "[+{}]", Frame::TRUNCATE), "中文 [+6]");
+		assert_eq!(cut_with("abcdefghijklmnop", 8, "+{}", Frame::MIDDLE), "abc+11op");
+	}
+
+	#[test]
+	fn ellipsis_count_changing_width_is_settled() {
+		// Nine characters hidden fit one digit, ten need two
+		let line = "abcdefghijklmnopqrs";
+		let truncated = cut_with(line, 11, "[+{}]", Frame::TRUNCATE);
+		let shown = truncated.split('[').next().unwrap().len();
+		assert_eq!(truncated, format!("{}[+{}]", &line[..shown], line.len() - shown));
+		assert_eq!(ansi::width(&truncated), 11);
+	}
+
+	#[test]
+	fn middle_pads_beside_the_ellipsis() {
+		assert_eq!(cut("/usr/local/share/bin/tool", 18, Frame::MIDDLE), "/usr/loc...in/tool");
+		assert_eq!(cut("中文中文中文中文", 9, Frame::MIDDLE), "中 ... 文");
+	}
+
+	#[test]
+	fn url_keeps_scheme_and_host() {
+		let url = "https://example.com/docs/guide/index.html?x=1";
+		assert_eq!(cut(url, 40, Frame::URL), "https://example.com/.../index.html?x=1  ");
+		assert_eq!(cut(url, 30, Frame::URL), "https://example.com/...tml?x=1");
+		assert_eq!(cut("https://example.com?query=long", 25, Frame::URL), "https://example.com...ong");
+		// Only a host too long to keep has its middle cut out
+		assert_eq!(cut(url, 15, Frame::URL), "https:...ml?x=1");
+	}
+
+	#[test]
+	fn path_is_padded_to_the_width() {
+		assert_eq!(cut("/usr/local/share/bin/tool", 18, Frame::PATH), "/usr/.../bin/tool ");
+		assert_eq!(cut("/usr/local/share/bin/a_long_tool", 14, Frame::PATH), "/usr/...g_tool");
+	}
+
+	#[test]
+	fn styles_are_closed_and_reopened() {
+		let line = "\x1b[31mred text that is long\x1b[0m tail";
+		assert_eq!(cut(line, 10, Frame::TRUNCATE), "\x1b[31mred tex\x1b[0m...");
+		assert_eq!(cut(line, 10, Frame::CHOP), "...\x1b[31mng\x1b[0m tail");
+	}
+
+	#[test]
+	fn every_line_is_truncated() {
+		assert_eq!(cut("first line here\nsecond line here", 8, Frame::TRUNCATE), "first...\nsecon...");
+	}
+
+	#[test]
+	fn justify_fills_every_line_but_the_last() {
+		let text = "The quick brown fox jumps over the lazy dog";
+		assert_eq!(justify(Some(text), Some(16), None, None, None), "The  quick brown\nfox  jumps  over\nthe lazy dog");
+		assert_eq!(justify(Some(text), Some(16), Some(Alignment::RIGHT), None, None), "The  quick brown\nfox  jumps  over\n    the lazy dog");
+		assert_eq!(justify(Some(text), Some(16), Some(Alignment::JUSTIFY), None, None), "The  quick brown\nfox  jumps  over\nthe   lazy   dog");
+	}
+
+	#[test]
+	fn justify_measures_display_width() {
+		assert_eq!(justify_line("中文 a 中文", 12), "中文  a 中文");
+		assert_eq!(justify_line("word", 10), "word");
+	}
+
+	#[test]
+	fn reflow_joins_paragraphs_and_keeps_list_markers() {
+		let text = "One two\nthree four five.\n\n  - item one\n    wraps here\n  - item two\n1. numbered";
+		assert_eq!(
+			wrap(Some(text), Some(14), Some(true), None),
+			"One two three\nfour five.\n\n  - item one\n    wraps here\n  - item two\n1. numbered",
+		);
+	}
+
+	#[test]
+	fn reflow_keeps_code_blocks() {
+		let text = "Some text\n```\nlet  x = 1; // a long line of code\n```\nmore text";
+		assert_eq!(wrap(Some(text), Some(12), Some(true), None), "Some text\n```\nlet  x = 1; // a long line of code\n```\nmore text");
+	}
+
+	#[test]
+	fn comment_prefixes_are_kept() {
+		let text = "    // A comment that wraps\n    // here.\n    //\n    // Next.";
+		assert_eq!(
+			wrap(Some(text), Some(20), Some(true), Some(true)),
+			"    // A comment\n    // that wraps\n    // here.\n    //\n    // Next.",
+		);
+		assert_eq!(wrap(Some("# one two three four"), Some(12), None, Some(true)), "# one two\n# three four");
+	}
+
+	#[test]
+	fn comments_are_reflowed_without_reflow() {
+		let text = "// A comment that\n// wraps here and goes on.";
+		assert_eq!(wrap(Some(text), Some(20), None, Some(true)), "// A comment that\n// wraps here and\n// goes on.");
+		assert_eq!(wrap(Some("//"), Some(20), None, Some(true)), "//");
+	}
+
+	#[test]
+	fn lists_and_dashes_are_not_comments() {
+		let text = "- first item of a list\n* star item";
+		assert_eq!(wrap(Some(text), Some(20), Some(true), Some(true)), "- first item of\n  a list\n* star item");
+		assert_eq!(wrap(Some("-- signed off by Bob Smith"), Some(20), None, Some(true)), "-- signed off by Bob\nSmith");
+	}
+
+	#[test]
+	fn block_comments_keep_their_stars() {
+		let text = "  /**\n   * Block comment text that is long enough\n   * to wrap.\n   *\n   * Second.\n   */";
+		assert_eq!(
+			wrap(Some(text), Some(24), None, Some(true)),
+			"  /**\n   * Block comment text\n   * that is long enough\n   * to wrap.\n   *\n   * Second.\n   */",
+		);
+	}
+
+	#[test]
+	fn nested_quotes_are_wrapped_apart() {
+		let text = "> outer quote text\n> > inner quote text\n>> more inner";
+		assert_eq!(
+			wrap(Some(text), Some(14), Some(true), Some(true)),
+			"> outer quote\n> text\n> > inner\n> > quote text\n> > more inner",
+		);
+	}
+
+	/// Formats `text` as a number, unaligned, after `setup` configures the formatter.
+	fn number(text: &str, setup: impl FnOnce(&mut TextFormatter)) -> String {
+		let mut formatter = TextFormatter::new(Some(text.to_string()));
+		formatter.set_alignment(Alignment::NONE);
+		setup(&mut formatter);
+		formatter.text()
+	}
+
+	#[test]
+	fn locales_set_separators_and_grouping() {
+		let grouped = |text: &str, locale: Locale| number(text, |f| { f.set_locale(locale).set_use_thousand_separator(true); });
+		assert_eq!(grouped("1234567,89", Locale::FR_FR), "1\u{202F}234\u{202F}567,89");
+		assert_eq!(grouped("1 234 567,89", Locale::FR_FR), "1\u{202F}234\u{202F}567,89");
+		assert_eq!(grouped("1234567.89", Locale::DE_CH), "1'234'567.89");
+		assert_eq!(grouped("1234567,89", Locale::DE_DE), "1.234.567,89");
+		assert_eq!(grouped("1234567.89", Locale::EN_IN), "12,34,567.89");
+		assert!("de-ch".parse::<Locale>() == Ok(Locale::DE_CH));
+	}
+
+	#[test]
+	fn indian_grouping() {
+		assert_eq!(group_digits("1234567", ',', Grouping::INDIAN), "12,34,567");
+		assert_eq!(group_digits("-123456789", ',', Grouping::INDIAN), "-12,34,56,789");
+		assert_eq!(group_digits("999", ',', Grouping::INDIAN), "999");
+		assert_eq!(group_digits("1234567", ',', Grouping::THOUSANDS), "1,234,567");
+	}
+
+	#[test]
+	fn input_locale_only_changes_reading() {
+		assert_eq!(number("1.234,5", |f| { f.set_input_locale(Locale::DE_DE); }), "1234.5");
+		assert_eq!(number("1.234,5", |f| { f.set_input_locale(Locale::DE_DE).set_use_thousand_separator(true); }), "1,234.5");
+		assert_eq!(
+			number("1.234,5", |f| { f.set_input_locale(Locale::DE_DE).set_locale(Locale::FR_FR).set_use_thousand_separator(true); }),
+			"1\u{202F}234,5",
+		);
+	}
+
+	#[test]
+	fn bytes_switch_units_at_1024() {
+		let bytes = |text: &str| number(text, |f| { f.set_number_display(NumberDisplay::BYTES); });
+		assert_eq!(bytes("1023"), "1023 B");
+		assert_eq!(bytes("1024"), "1 KiB");
+		assert_eq!(bytes("1536"), "1.5 KiB");
+		assert_eq!(bytes("1048575"), "1 MiB");
+		assert_eq!(bytes("-2048"), "-2 KiB");
+	}
+
+	#[test]
+	fn si_prefixes_round_up_to_the_next_prefix() {
+		let si = |text: &str, digits: usize| number(text, |f| {
+			f.set_number_display(NumberDisplay::SI).set_max_decimal_digits(digits).set_pad_decimal_digits(true);
+		});
+		assert_eq!(si("999", 1), "999.0");
+		assert_eq!(si("1000", 1), "1.0k");
+		assert_eq!(si("999500", 1), "999.5k");
+		assert_eq!(si("999950", 1), "1.0M");
+		assert_eq!(si("999500", 0), "1M");
+		assert_eq!(si("0.25", 0), "250m");
+	}
+
+	#[test]
+	fn durations_show_two_units() {
+		let duration = |text: &str| number(text, |f| { f.set_number_display(NumberDisplay::DURATION); });
+		assert_eq!(duration("11520"), "3h 12m");
+		assert_eq!(duration("90061"), "1d 1h");
+		assert_eq!(duration("3605"), "1h");
+		assert_eq!(duration("59.6"), "1m");
+		assert_eq!(duration("0.25"), "250ms");
+		assert_eq!(duration("0"), "0s");
+		assert_eq!(duration("-90"), "-1m 30s");
+	}
+
+	#[test]
+	fn accounting_wraps_negative_numbers_in_parentheses() {
+		let accounting = |text: &str, currency: Option<&str>| number(text, |f| {
+			f.set_accounting(true)
+				.set_pad_decimal_digits(true)
+				.set_use_thousand_separator(true)
+				.set_currency(currency.map(String::from));
+		});
+		assert_eq!(accounting("-1234", None), "(1,234.00)");
+		assert_eq!(accounting("-1234", Some("$")), "($1,234.00)");
+		assert_eq!(accounting("1234", Some("$")), "$1,234.00");
+	}
+
+	#[test]
+	fn currency_and_plus_sign() {
+		assert_eq!(
+			number("1234,5", |f| {
+				f.set_locale(Locale::DE_DE)
+					.set_use_thousand_separator(true)
+					.set_currency(Some("€".to_string()))
+					.set_currency_position(CurrencyPosition::SUFFIX);
+			}),
+			"1.234,5 €",
+		);
+		assert_eq!(number("5", |f| { f.set_plus_sign(true); }), "+5");
+		assert_eq!(number("-5", |f| { f.set_plus_sign(true); }), "-5");
+	}
+
+	#[test]
+	fn percent_multiplies_by_100() {
+		let percent = |text: &str| number(text, |f| { f.set_number_display(NumberDisplay::PERCENT); });
+		assert_eq!(percent("0.125"), "12.5%");
+		assert_eq!(percent("-0.5"), "-50%");
+		assert_eq!(percent("0.123456"), "12.35%");
+	}
+
+	#[test]
+	fn scientific_and_engineering_notation() {
+		let scientific = |text: &str, display: NumberDisplay| number(text, |f| { f.set_number_display(display); });
+		assert_eq!(scientific("12345", NumberDisplay::SCIENTIFIC), "1.23e4");
+		assert_eq!(scientific("0.000123", NumberDisplay::SCIENTIFIC), "1.23e-4");
+		assert_eq!(scientific("9.995", NumberDisplay::SCIENTIFIC), "1e1");
+		assert_eq!(scientific("12345", NumberDisplay::ENGINEERING), "12.35e3");
+		assert_eq!(scientific("0.000123", NumberDisplay::ENGINEERING), "123e-6");
+		assert_eq!(scientific("-1234567", NumberDisplay::ENGINEERING), "-1.23e6");
+	}
+
+	#[test]
+	fn numbers_rounding_to_zero_have_no_sign() {
+		let mut formatter = TextFormatter::new(Some("-0.001".to_string()));
+		formatter.set_pad_decimal_digits(true).set_max_decimal_digits(2).set_alignment(Alignment::NONE);
+		assert_eq!(formatter.clone().text(), "0.00");
+		assert_eq!(formatter.set_accounting(true).text(), "0.00");
+	}
+
+	#[test]
+	fn wrap_limits_lines() {
+		let mut formatter = TextFormatter::new(Some("one two three four five six".to_string()));
+		formatter.set_width(9).set_max_lines(Some(2)).set_ellipsis("[+{}]".to_string());
+		assert_eq!(formatter.wrap(), "one two\nthre[+13]");
+	}
+
+	#[test]
+	fn hyphenated_words_break_after_hyphens() {
+		assert_eq!(wrap_line("well-known words", 6), ["well-", "known", "words"]);
+		assert_eq!(wrap_line("a well-known fact", 8), ["a well-", "known", "fact"]);
+		assert_eq!(wrap_line("greenish-long-name", 8), ["greenish", "-long-", "name"]);
+	}
+
+	#[test]
+	fn long_words_break_between_clusters() {
+		assert_eq!(wrap_line("abcdefghij k", 4), ["abcd", "efgh", "ij k"]);
+		assert_eq!(wrap_line("e\u{301}e\u{301}e\u{301}", 2), ["e\u{301}e\u{301}", "e\u{301}"]);
+	}
 }